@@ -1,265 +1,140 @@
-use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
-use dashmap::DashMap;
-use indexmap::IndexMap;
-use rand::{Rng, SeedableRng};
-use rustc_hash::FxHashMap;
-use smallvec::SmallVec;
-use std::collections::VecDeque;
-use std::collections::{BTreeMap, HashMap};
-
-fn vec_find(data: &[(usize, Vec<u8>)], id: usize) {
-    data.iter().find(|(i, _)| i == &id);
-}
-
-fn hashmap_find(data: &HashMap<usize, Vec<u8>>, id: usize) {
-    data.get(&id);
-}
-
-fn btree_find(data: &BTreeMap<usize, Vec<u8>>, id: usize) {
-    data.get(&id);
-}
-
-fn dashmap_find(data: &DashMap<usize, Vec<u8>>, id: usize) {
-    data.get(&id);
-}
-
-fn indexmap_find(data: &IndexMap<usize, Vec<u8>>, id: usize) {
-    data.get(&id);
+use criterion::{
+    BenchmarkGroup, Criterion, black_box, criterion_group, criterion_main, measurement::WallTime,
+};
+use napkin_benches::{
+    affinity,
+    groups::{COLLECTIONS_FIND, COLLECTIONS_FIND_LOOKUPS},
+    hwinfo,
+    impls::collections::{self, BenchCollection, Visitor},
+    params::Params,
+    probe, profile,
+    workload::{self, KeyDistribution, KeyPosition, Payload},
+};
+use std::cell::OnceCell;
+
+/// Hit ratio and position the key distributions other than dense are
+/// measured at by default.
+const REFERENCE_LOOKUPS: (usize, KeyPosition) = (100, KeyPosition::Middle);
+
+/// Registers the lookups of each point in every collection holding `keys`,
+/// built once when the first of them runs, so listing or filtering them out
+/// builds nothing.
+struct Find<'a, 'g> {
+    group: &'a mut BenchmarkGroup<'g, WallTime>,
+    keys: &'a [usize],
+    data: &'a Payload,
+    points: &'a [(Params, Vec<usize>)],
 }
 
-fn smallvec_find_1(data: &SmallVec<[(usize, Vec<u8>); 1]>, id: usize) {
-    data.iter().find(|(i, _)| i == &id);
-}
-
-fn smallvec_find_4(data: &SmallVec<[(usize, Vec<u8>); 4]>, id: usize) {
-    data.iter().find(|(i, _)| i == &id);
-}
-
-fn smallvec_find_8(data: &SmallVec<[(usize, Vec<u8>); 8]>, id: usize) {
-    data.iter().find(|(i, _)| i == &id);
-}
-
-fn smallvec_find_16(data: &SmallVec<[(usize, Vec<u8>); 16]>, id: usize) {
-    data.iter().find(|(i, _)| i == &id);
-}
-
-fn smallvec_find_32(data: &SmallVec<[(usize, Vec<u8>); 32]>, id: usize) {
-    data.iter().find(|(i, _)| i == &id);
-}
-
-fn vecdeque_find(data: &VecDeque<(usize, Vec<u8>)>, id: usize) {
-    data.iter().find(|(i, _)| i == &id);
-}
-
-fn fxhashmap_find(data: &FxHashMap<usize, Vec<u8>>, id: usize) {
-    data.get(&id);
+impl Visitor for Find<'_, '_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let collection = OnceCell::new();
+        for (param, lookups) in self.points {
+            let id = COLLECTIONS_FIND.id(&format!("{} Find", C::NAME), param);
+            self.group.throughput(COLLECTIONS_FIND.throughput(param));
+            self.group
+                .bench_with_input(id.criterion(), lookups, |b, lookups| {
+                    let collection = collection
+                        .get_or_init(|| C::build(workload::entries(self.keys, self.data)));
+                    probe::iter(b, &id, || {
+                        for &key in lookups {
+                            black_box(black_box(collection).find(black_box(key)));
+                        }
+                    });
+                });
+        }
+    }
 }
 
 fn vec_vs_hashmap(c: &mut Criterion) {
-    let data_sizes = [
-        8 * 1024,         // 8 KB
-        512 * 1024,       // 512 KB
-        16 * 1024 * 1024, // 16 MB
-    ];
-    let data_lengths = [10, 100, 1000];
-
-    let mut group = c.benchmark_group("collections-find");
-    let mut rng = rand::rngs::StdRng::seed_from_u64(64);
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
+
+    let data_lengths = COLLECTIONS_FIND.sweep("length", &[10, 100, 1000]);
+    let hit_pcts = COLLECTIONS_FIND.sweep("hit_pct", &[0, 50, 100]);
+    let positions = COLLECTIONS_FIND
+        .sweep("position", &KeyPosition::ALL.map(KeyPosition::value))
+        .into_iter()
+        .map(|value| {
+            KeyPosition::from_value(value)
+                .unwrap_or_else(|| panic!("{} has no position {value}", COLLECTIONS_FIND.name))
+        })
+        .collect::<Vec<_>>();
+    let distributions = COLLECTIONS_FIND
+        .sweep("keys", &KeyDistribution::ALL.map(KeyDistribution::value))
+        .into_iter()
+        .map(|value| {
+            KeyDistribution::from_value(value).unwrap_or_else(|| {
+                panic!("{} has no key distribution {value}", COLLECTIONS_FIND.name)
+            })
+        })
+        .collect::<Vec<_>>();
+    let data_sizes = COLLECTIONS_FIND.payload_sizes(&data_lengths);
+    // By default, hit ratios and positions are only crossed with dense keys,
+    // and the other distributions only measured at `REFERENCE_LOOKUPS`, which
+    // keeps the grid from multiplying out. Configuring any of the three
+    // crosses them in full.
+    let full_cross = ["hit_pct", "position", "keys"]
+        .iter()
+        .any(|dim| COLLECTIONS_FIND.configured(dim));
+    let crossed = hit_pcts
+        .iter()
+        .flat_map(|&hit_pct| positions.iter().map(move |&position| (hit_pct, position)))
+        .collect::<Vec<_>>();
+    let lookup_points = |distribution| match distribution {
+        KeyDistribution::Dense => crossed.as_slice(),
+        _ if full_cross => crossed.as_slice(),
+        _ => &[REFERENCE_LOOKUPS],
+    };
+
+    let mut group = c.benchmark_group(COLLECTIONS_FIND.name);
+    let mut rng = workload::rng();
 
     for &data_size in &data_sizes {
-        let data = vec![1u8; data_size];
-        for data_len in data_lengths {
-            let index_to_find = (rng.random::<u64>() % data_len as u64) as usize;
-
-            //  we create a vector of data
-            let vec_data = (0..data_len).map(|i| (i, data.clone())).collect::<Vec<_>>();
-
-            //  we create a hashmap of data
-            let hashmap_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<HashMap<_, _>>();
-
-            //  we create a btree of data
-            let btree_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<BTreeMap<_, _>>();
-
-            //  we create a dashmap of data
-            let dashmap_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<DashMap<_, _>>();
-
-            //  we create a indexmap of data
-            let indexmap_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<IndexMap<_, _>>();
-
-            //  we create a smallvec of data
-            let smallvec_1_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<SmallVec<[(usize, Vec<u8>); 1]>>();
-            let smallvec_4_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<SmallVec<[(usize, Vec<u8>); 4]>>();
-            let smallvec_8_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<SmallVec<[(usize, Vec<u8>); 8]>>();
-            let smallvec_16_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<SmallVec<[(usize, Vec<u8>); 16]>>();
-            let smallvec_32_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<SmallVec<[(usize, Vec<u8>); 32]>>();
-
-            //  we create a vecdeque of data
-            let vecdeque_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<VecDeque<_>>();
-
-            //  we create a fxhashmap of data
-            let fxhashmap_data = (0..data_len)
-                .map(|i| (i, data.clone()))
-                .collect::<FxHashMap<_, _>>();
-
-            //  we create a benchmark for the vector
-            group.bench_with_input(
-                BenchmarkId::new("Vec Find", format!("{}KBx{data_len}", data_size / 1024)),
-                &(vec_data, index_to_find),
-                |b, (vec_data, index_to_find)| {
-                    b.iter(|| vec_find(black_box(vec_data), black_box(*index_to_find)));
-                },
-            );
-
-            //  we create a benchmark for the hashmap
-            group.bench_with_input(
-                BenchmarkId::new("HashMap Find", format!("{}KBx{data_len}", data_size / 1024)),
-                &(hashmap_data, index_to_find),
-                |b, (hashmap_data, index_to_find)| {
-                    b.iter(|| hashmap_find(black_box(hashmap_data), black_box(*index_to_find)));
-                },
-            );
-
-            //  we create a benchmark for the btree
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "BTreeMap Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(btree_data, index_to_find),
-                |b, (btree_data, index_to_find)| {
-                    b.iter(|| btree_find(black_box(btree_data), black_box(*index_to_find)));
-                },
-            );
-
-            //  we create a benchmark for the dashmap
-            group.bench_with_input(
-                BenchmarkId::new("DashMap Find", format!("{}KBx{data_len}", data_size / 1024)),
-                &(dashmap_data, index_to_find),
-                |b, (dashmap_data, index_to_find)| {
-                    b.iter(|| dashmap_find(black_box(dashmap_data), black_box(*index_to_find)));
-                },
-            );
-            //  we create a benchmark for the indexmap
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "IndexMap Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(indexmap_data, index_to_find),
-                |b, (indexmap_data, index_to_find)| {
-                    b.iter(|| indexmap_find(black_box(indexmap_data), black_box(*index_to_find)));
-                },
-            );
-
-            //  we create a benchmark for the smallvec
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "SmallVec<1> Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(smallvec_1_data, index_to_find),
-                |b, (smallvec_1_data, index_to_find)| {
-                    b.iter(|| {
-                        smallvec_find_1(black_box(smallvec_1_data), black_box(*index_to_find))
-                    });
-                },
-            );
-
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "SmallVec<4> Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(smallvec_4_data, index_to_find),
-                |b, (smallvec_4_data, index_to_find)| {
-                    b.iter(|| {
-                        smallvec_find_4(black_box(smallvec_4_data), black_box(*index_to_find))
-                    });
-                },
-            );
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "SmallVec<8> Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(smallvec_8_data, index_to_find),
-                |b, (smallvec_8_data, index_to_find)| {
-                    b.iter(|| {
-                        smallvec_find_8(black_box(smallvec_8_data), black_box(*index_to_find))
-                    });
-                },
-            );
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "SmallVec<16> Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(smallvec_16_data, index_to_find),
-                |b, (smallvec_16_data, index_to_find)| {
-                    b.iter(|| {
-                        smallvec_find_16(black_box(smallvec_16_data), black_box(*index_to_find))
-                    });
-                },
-            );
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "SmallVec<32> Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(smallvec_32_data, index_to_find),
-                |b, (smallvec_32_data, index_to_find)| {
-                    b.iter(|| {
-                        smallvec_find_32(black_box(smallvec_32_data), black_box(*index_to_find))
-                    });
-                },
-            );
-            //  we create a benchmark for the vecdeque
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "VecDeque Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(vecdeque_data, index_to_find),
-                |b, (vecdeque_data, index_to_find)| {
-                    b.iter(|| vecdeque_find(black_box(vecdeque_data), black_box(*index_to_find)));
-                },
-            );
-            //  we create a benchmark for the fxhashmap
-            group.bench_with_input(
-                BenchmarkId::new(
-                    "FxHashMap Find",
-                    format!("{}KBx{data_len}", data_size / 1024),
-                ),
-                &(fxhashmap_data, index_to_find),
-                |b, (fxhashmap_data, index_to_find)| {
-                    b.iter(|| fxhashmap_find(black_box(fxhashmap_data), black_box(*index_to_find)));
-                },
-            );
+        let data = workload::payload(data_size, 1);
+        for &data_len in &data_lengths {
+            for &distribution in &distributions {
+                // the keys past `data_len` are the ones lookups miss with
+                let all_keys = distribution.keys(&mut rng, data_len + COLLECTIONS_FIND_LOOKUPS);
+                let (keys, absent) = all_keys.split_at(data_len);
+                // Lookups that all miss are the same whatever the hit ratio or
+                // position, so only the first such pair is measured. With the
+                // default sweeps that is 0% hits at `absent`.
+                let mut measured_misses = false;
+                let mut points = Vec::new();
+                for &(hit_pct, position) in lookup_points(distribution) {
+                    let lookups = workload::lookups(&mut rng, keys, absent, hit_pct, position);
+                    if lookups.iter().all(|key| absent.contains(key)) {
+                        if measured_misses {
+                            continue;
+                        }
+                        measured_misses = true;
+                    }
+                    let param = COLLECTIONS_FIND.params(&[
+                        data_size / 1024,
+                        data_len,
+                        hit_pct,
+                        position.value(),
+                        distribution.value(),
+                    ]);
+                    points.push((param, lookups));
+                }
+                collections::visit_all(&mut Find {
+                    group: &mut group,
+                    keys,
+                    data: &data,
+                    points: &points,
+                });
+            }
         }
     }
 
-    group.finish();
+    probe::finish(group);
 }
 
-criterion_group!(collections_find, vec_vs_hashmap);
+criterion_group! {
+    name = collections_find;
+    config = profile::criterion();
+    targets = vec_vs_hashmap
+}
 criterion_main!(collections_find);
@@ -0,0 +1,80 @@
+use criterion::{
+    BatchSize, BenchmarkGroup, Criterion, black_box, criterion_group, criterion_main,
+    measurement::WallTime,
+};
+use napkin_benches::{
+    affinity,
+    groups::{COLLECTIONS_INSERT, COLLECTIONS_PAYLOAD},
+    hwinfo,
+    impls::collections::{self, BenchCollection, Entry, Visitor},
+    params::Params,
+    probe, profile, workload,
+};
+
+/// Registers inserting `entries`, in order, into an empty collection.
+struct Insert<'a, 'g> {
+    group: &'a mut BenchmarkGroup<'g, WallTime>,
+    entries: &'a [Entry],
+    param: &'a Params,
+    presized: bool,
+}
+
+impl Visitor for Insert<'_, '_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let id = COLLECTIONS_INSERT.id(&format!("{} Insert", C::NAME), self.param);
+        self.group
+            .throughput(COLLECTIONS_INSERT.throughput(self.param));
+        self.group.bench_function(id.criterion(), |b| {
+            probe::iter_batched(
+                b,
+                &id,
+                // cloning the payloads is not part of inserting them
+                || {
+                    let collection = collections::empty::<C>(self.entries.len(), self.presized);
+                    (collection, self.entries.to_vec())
+                },
+                |(mut collection, entries)| {
+                    for (key, payload) in entries {
+                        collection.insert(black_box(key), payload);
+                    }
+                    collection
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+}
+
+fn insert_every_key(c: &mut Criterion) {
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
+
+    let lengths = COLLECTIONS_INSERT.sweep("length", &[10, 100, 1000, 10_000]);
+    let presized = COLLECTIONS_INSERT.sweep("presized", &[0, 1]);
+
+    let mut group = c.benchmark_group(COLLECTIONS_INSERT.name);
+    let mut rng = workload::rng();
+    let data = workload::payload(COLLECTIONS_PAYLOAD, 1);
+
+    for &length in &lengths {
+        let keys = workload::random_keys(&mut rng, length, length * 16);
+        let entries = workload::entries(&keys, &data).collect::<Vec<_>>();
+        for &presized in &presized {
+            collections::visit_all(&mut Insert {
+                group: &mut group,
+                entries: &entries,
+                param: &COLLECTIONS_INSERT.params(&[length, presized]),
+                presized: presized != 0,
+            });
+        }
+    }
+
+    probe::finish(group);
+}
+
+criterion_group! {
+    name = collections_insert;
+    config = profile::criterion();
+    targets = insert_every_key
+}
+criterion_main!(collections_insert);
@@ -0,0 +1,70 @@
+use criterion::{
+    BenchmarkGroup, Criterion, black_box, criterion_group, criterion_main, measurement::WallTime,
+};
+use napkin_benches::{
+    affinity,
+    groups::COLLECTIONS_ITERATE,
+    hwinfo,
+    impls::collections::{self, BenchCollection, Visitor},
+    params::Params,
+    probe, profile,
+    workload::{self, Payload},
+};
+use std::cell::OnceCell;
+
+/// Registers a full scan of every collection holding `keys`, which only reads
+/// what the collection itself stores. `collections-sum` also reads every
+/// payload.
+struct Iterate<'a, 'g> {
+    group: &'a mut BenchmarkGroup<'g, WallTime>,
+    keys: &'a [usize],
+    data: &'a Payload,
+    param: &'a Params,
+}
+
+impl Visitor for Iterate<'_, '_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let id = COLLECTIONS_ITERATE.id(&format!("{} Visit", C::NAME), self.param);
+        let collection = OnceCell::new();
+        self.group
+            .throughput(COLLECTIONS_ITERATE.throughput(self.param));
+        self.group.bench_function(id.criterion(), |b| {
+            let collection =
+                collection.get_or_init(|| C::build(workload::entries(self.keys, self.data)));
+            probe::iter(b, &id, || collections::sum_keys(black_box(collection)));
+        });
+    }
+}
+
+fn full_scans(c: &mut Criterion) {
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
+
+    let data_lengths = COLLECTIONS_ITERATE.sweep("length", &[10, 100, 1000]);
+    let data_sizes = COLLECTIONS_ITERATE.payload_sizes(&data_lengths);
+
+    let mut group = c.benchmark_group(COLLECTIONS_ITERATE.name);
+    let mut rng = workload::rng();
+
+    for &data_size in &data_sizes {
+        let data = workload::payload(data_size, 1);
+        for &data_len in &data_lengths {
+            let keys = workload::random_keys(&mut rng, data_len, data_len * 16);
+            collections::visit_all(&mut Iterate {
+                group: &mut group,
+                keys: &keys,
+                data: &data,
+                param: &COLLECTIONS_ITERATE.params(&[data_size / 1024, data_len]),
+            });
+        }
+    }
+
+    probe::finish(group);
+}
+
+criterion_group! {
+    name = collections_iterate;
+    config = profile::criterion();
+    targets = full_scans
+}
+criterion_main!(collections_iterate);
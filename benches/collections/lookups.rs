@@ -0,0 +1,102 @@
+use criterion::{
+    BenchmarkGroup, Criterion, black_box, criterion_group, criterion_main, measurement::WallTime,
+};
+use napkin_benches::{
+    affinity,
+    groups::{COLLECTIONS_LOOKUPS, COLLECTIONS_LOOKUPS_BATCH, COLLECTIONS_PAYLOAD},
+    hwinfo,
+    impls::collections::{self, BenchCollection, Visitor},
+    params::Params,
+    probe, profile,
+    workload::{self, LookupPattern, Payload},
+};
+use std::cell::OnceCell;
+
+/// Registers the lookup stream of each point in every collection holding
+/// `keys`, built once when the first of them runs, so listing or filtering
+/// them out builds nothing.
+struct Lookups<'a, 'g> {
+    group: &'a mut BenchmarkGroup<'g, WallTime>,
+    keys: &'a [usize],
+    data: &'a Payload,
+    points: &'a [(Params, Vec<usize>)],
+}
+
+impl Visitor for Lookups<'_, '_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let collection = OnceCell::new();
+        for (param, stream) in self.points {
+            let id = COLLECTIONS_LOOKUPS.id(&format!("{} Find", C::NAME), param);
+            self.group.throughput(COLLECTIONS_LOOKUPS.throughput(param));
+            self.group
+                .bench_with_input(id.criterion(), stream, |b, stream| {
+                    let collection = collection
+                        .get_or_init(|| C::build(workload::entries(self.keys, self.data)));
+                    probe::iter(b, &id, || {
+                        for &key in stream {
+                            black_box(black_box(collection).find(black_box(key)));
+                        }
+                    });
+                });
+        }
+    }
+}
+
+fn lookup_streams(c: &mut Criterion) {
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
+
+    let lengths = COLLECTIONS_LOOKUPS.sweep("length", &[10, 100, 1000, 10_000]);
+    let patterns = COLLECTIONS_LOOKUPS
+        .sweep("pattern", &LookupPattern::ALL.map(LookupPattern::value))
+        .into_iter()
+        .map(|value| {
+            LookupPattern::from_value(value).unwrap_or_else(|| {
+                panic!("{} has no lookup pattern {value}", COLLECTIONS_LOOKUPS.name)
+            })
+        })
+        .collect::<Vec<_>>();
+    let skews = COLLECTIONS_LOOKUPS.sweep("skew_pct", &[50, 99, 150]);
+
+    let mut group = c.benchmark_group(COLLECTIONS_LOOKUPS.name);
+    let mut rng = workload::rng();
+    let data = workload::payload(COLLECTIONS_PAYLOAD, 1);
+
+    for &length in &lengths {
+        let keys = workload::random_keys(&mut rng, length, length * 16);
+        let mut points = Vec::new();
+        for &pattern in &patterns {
+            // only Zipf streams sweep the skew
+            let skews = match pattern {
+                LookupPattern::Zipf => skews.as_slice(),
+                _ => &[0],
+            };
+            for &skew_pct in skews {
+                let stream = workload::lookup_stream(
+                    &mut rng,
+                    &keys,
+                    COLLECTIONS_LOOKUPS_BATCH,
+                    pattern,
+                    skew_pct as f64 / 100.0,
+                );
+                let param = COLLECTIONS_LOOKUPS.params(&[length, pattern.value(), skew_pct]);
+                points.push((param, stream));
+            }
+        }
+        collections::visit_all(&mut Lookups {
+            group: &mut group,
+            keys: &keys,
+            data: &data,
+            points: &points,
+        });
+    }
+
+    probe::finish(group);
+}
+
+criterion_group! {
+    name = collections_lookups;
+    config = profile::criterion();
+    targets = lookup_streams
+}
+criterion_main!(collections_lookups);
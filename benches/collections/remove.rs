@@ -0,0 +1,95 @@
+use criterion::{
+    BatchSize, BenchmarkGroup, Criterion, black_box, criterion_group, criterion_main,
+    measurement::WallTime,
+};
+use napkin_benches::{
+    affinity,
+    groups::{COLLECTIONS_PAYLOAD, COLLECTIONS_REMOVE},
+    hwinfo,
+    impls::collections::{self, BenchCollection, Entry, Visitor},
+    params::Params,
+    probe, profile, workload,
+};
+
+/// Registers removing every key, in `order`, from a collection filled with
+/// `entries`, once with `remove` and, where it differs, once with
+/// `swap_remove`.
+struct Remove<'a, 'g> {
+    group: &'a mut BenchmarkGroup<'g, WallTime>,
+    entries: &'a [Entry],
+    order: &'a [usize],
+    param: &'a Params,
+    presized: bool,
+}
+
+impl Remove<'_, '_> {
+    fn bench<C: BenchCollection>(&mut self, function: &str, remove: fn(&mut C, usize)) {
+        let id = COLLECTIONS_REMOVE.id(&format!("{} {function}", C::NAME), self.param);
+        self.group
+            .throughput(COLLECTIONS_REMOVE.throughput(self.param));
+        self.group.bench_function(id.criterion(), |b| {
+            probe::iter_batched(
+                b,
+                &id,
+                || collections::filled::<C>(self.entries, self.presized),
+                |mut collection| {
+                    for &key in self.order {
+                        remove(&mut collection, black_box(key));
+                    }
+                    collection
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+}
+
+impl Visitor for Remove<'_, '_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        self.bench::<C>("Remove", |c, key| {
+            black_box(c.remove(key));
+        });
+        if C::SWAP_REMOVE {
+            self.bench::<C>("SwapRemove", |c, key| {
+                black_box(c.swap_remove(key));
+            });
+        }
+    }
+}
+
+fn remove_every_key(c: &mut Criterion) {
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
+
+    let lengths = COLLECTIONS_REMOVE.sweep("length", &[10, 100, 1000, 10_000]);
+    let presized = COLLECTIONS_REMOVE.sweep("presized", &[0, 1]);
+
+    let mut group = c.benchmark_group(COLLECTIONS_REMOVE.name);
+    let mut rng = workload::rng();
+    let data = workload::payload(COLLECTIONS_PAYLOAD, 1);
+
+    for &length in &lengths {
+        let keys = workload::random_keys(&mut rng, length, length * 16);
+        let entries = workload::entries(&keys, &data).collect::<Vec<_>>();
+        // removing in insertion order would always hit the front of sequences
+        let order = workload::shuffled(&mut rng, &keys);
+        for &presized in &presized {
+            collections::visit_all(&mut Remove {
+                group: &mut group,
+                entries: &entries,
+                order: &order,
+                param: &COLLECTIONS_REMOVE.params(&[length, presized]),
+                presized: presized != 0,
+            });
+        }
+    }
+
+    probe::finish(group);
+}
+
+criterion_group! {
+    name = collections_remove;
+    config = profile::criterion();
+    targets = remove_every_key
+}
+criterion_main!(collections_remove);
@@ -0,0 +1,70 @@
+use criterion::{
+    BenchmarkGroup, Criterion, black_box, criterion_group, criterion_main, measurement::WallTime,
+};
+use napkin_benches::{
+    affinity,
+    groups::COLLECTIONS_SUM,
+    hwinfo,
+    impls::collections::{self, BenchCollection, Visitor},
+    params::Params,
+    probe, profile,
+    workload::{self, Payload},
+};
+use std::cell::OnceCell;
+
+/// Registers a full scan of every collection holding `keys` that sums every
+/// byte of every payload, streaming `keys.len() × data.len()` bytes through
+/// the caches on top of `collections-iterate`'s traversal.
+struct Sum<'a, 'g> {
+    group: &'a mut BenchmarkGroup<'g, WallTime>,
+    keys: &'a [usize],
+    data: &'a Payload,
+    param: &'a Params,
+}
+
+impl Visitor for Sum<'_, '_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let id = COLLECTIONS_SUM.id(&format!("{} Sum", C::NAME), self.param);
+        let collection = OnceCell::new();
+        self.group
+            .throughput(COLLECTIONS_SUM.throughput(self.param));
+        self.group.bench_function(id.criterion(), |b| {
+            let collection =
+                collection.get_or_init(|| C::build(workload::entries(self.keys, self.data)));
+            probe::iter(b, &id, || collections::sum_payloads(black_box(collection)));
+        });
+    }
+}
+
+fn payload_sums(c: &mut Criterion) {
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
+
+    let data_lengths = COLLECTIONS_SUM.sweep("length", &[10, 100, 1000]);
+    let data_sizes = COLLECTIONS_SUM.payload_sizes(&data_lengths);
+
+    let mut group = c.benchmark_group(COLLECTIONS_SUM.name);
+    let mut rng = workload::rng();
+
+    for &data_size in &data_sizes {
+        let data = workload::payload(data_size, 1);
+        for &data_len in &data_lengths {
+            let keys = workload::random_keys(&mut rng, data_len, data_len * 16);
+            collections::visit_all(&mut Sum {
+                group: &mut group,
+                keys: &keys,
+                data: &data,
+                param: &COLLECTIONS_SUM.params(&[data_size / 1024, data_len]),
+            });
+        }
+    }
+
+    probe::finish(group);
+}
+
+criterion_group! {
+    name = collections_sum;
+    config = profile::criterion();
+    targets = payload_sums
+}
+criterion_main!(collections_sum);
@@ -0,0 +1,78 @@
+use criterion::{
+    BenchmarkGroup, Criterion, black_box, criterion_group, criterion_main, measurement::WallTime,
+};
+use napkin_benches::{
+    affinity,
+    groups::{COLLECTIONS_PAYLOAD, COLLECTIONS_UPDATE},
+    hwinfo,
+    impls::collections::{self, BenchCollection, Entry, Visitor},
+    params::Params,
+    probe, profile, workload,
+};
+
+/// Registers updating every key, in `order`, in place in a collection filled
+/// with `entries` when the benchmark first runs.
+struct Update<'a, 'g> {
+    group: &'a mut BenchmarkGroup<'g, WallTime>,
+    entries: &'a [Entry],
+    order: &'a [usize],
+    param: &'a Params,
+    presized: bool,
+}
+
+impl Visitor for Update<'_, '_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let mut collection = None;
+        let id = COLLECTIONS_UPDATE.id(&format!("{} Update", C::NAME), self.param);
+        self.group
+            .throughput(COLLECTIONS_UPDATE.throughput(self.param));
+        self.group.bench_function(id.criterion(), |b| {
+            let collection = collection
+                .get_or_insert_with(|| collections::filled::<C>(self.entries, self.presized));
+            probe::iter(b, &id, || {
+                for &key in self.order {
+                    black_box(collection.update(black_box(key), |payload| {
+                        payload[0] = payload[0].wrapping_add(1);
+                    }));
+                }
+            });
+        });
+    }
+}
+
+fn update_every_key(c: &mut Criterion) {
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
+
+    let lengths = COLLECTIONS_UPDATE.sweep("length", &[10, 100, 1000, 10_000]);
+    let presized = COLLECTIONS_UPDATE.sweep("presized", &[0, 1]);
+
+    let mut group = c.benchmark_group(COLLECTIONS_UPDATE.name);
+    let mut rng = workload::rng();
+    let data = workload::payload(COLLECTIONS_PAYLOAD, 1);
+
+    for &length in &lengths {
+        let keys = workload::random_keys(&mut rng, length, length * 16);
+        let entries = workload::entries(&keys, &data).collect::<Vec<_>>();
+        // updating in insertion order would favour sequences' prefetching
+        let order = workload::shuffled(&mut rng, &keys);
+        for &presized in &presized {
+            collections::visit_all(&mut Update {
+                group: &mut group,
+                entries: &entries,
+                order: &order,
+                param: &COLLECTIONS_UPDATE.params(&[length, presized]),
+                presized: presized != 0,
+            });
+        }
+    }
+
+    probe::finish(group);
+}
+
+criterion_group! {
+    name = collections_update;
+    config = profile::criterion();
+    targets = update_every_key
+}
+criterion_main!(collections_update);
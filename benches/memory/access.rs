@@ -1,64 +1,75 @@
-use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
-use rand::{prelude::*, rng};
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use napkin_benches::{
+    affinity,
+    groups::{MEMORY_ACCESS, MEMORY_ACCESS_STRIDE},
+    hwinfo::{self, HardwareProfile},
+    impls::memory_access::{random_access, sequential_access, stride_access},
+    probe, profile, workload,
+};
 
-fn sequential_access(data: &mut [u8]) {
-    for d in data {
-        *d = d.wrapping_add(1);
-    }
-}
-
-fn random_access(data: &mut [u8], indices: &[usize]) {
-    for &i in indices {
-        data[i] = data[i].wrapping_add(1);
-    }
-}
+fn memory_access_benchmark(c: &mut Criterion) {
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
 
-fn stride_access(data: &mut [u8], stride: usize) {
-    let len = data.len();
-    for i in (0..len).step_by(stride) {
-        data[i] = data[i].wrapping_add(1);
-    }
-}
+    let mut group = c.benchmark_group(MEMORY_ACCESS.name);
 
-fn memory_access_benchmark(c: &mut Criterion) {
-    let mut group = c.benchmark_group("memory-access");
+    // Define data sizes (in bytes) around each cache level. Random access also
+    // keeps a usize index per byte, so each size costs ~9x its footprint.
+    let profile = HardwareProfile::detect();
+    let default_sizes = profile
+        .cache_sweep(profile.memory_budget(16))
+        .unwrap_or_else(|| {
+            vec![
+                8 * 1024,         // 8 KB
+                64 * 1024,        // 64 KB
+                512 * 1024,       // 512 KB
+                4 * 1024 * 1024,  // 4 MB
+                16 * 1024 * 1024, // 16 MB
+            ]
+        });
+    let sizes = MEMORY_ACCESS.sweep("size_bytes", &default_sizes);
 
-    // Define data sizes (in bytes)
-    let sizes = [
-        8 * 1024,         // 8 KB
-        64 * 1024,        // 64 KB
-        512 * 1024,       // 512 KB
-        4 * 1024 * 1024,  // 4 MB
-        16 * 1024 * 1024, // 16 MB
-    ];
+    let mut rng = workload::rng();
 
     for &size in &sizes {
+        let param = MEMORY_ACCESS.params(&[size]);
+        group.throughput(MEMORY_ACCESS.throughput(&param));
+
         // Prepare data
         let mut data = vec![0u8; size];
 
         // Sequential Access
-        group.bench_with_input(BenchmarkId::new("Sequential", size), &size, |b, &_size| {
-            b.iter(|| sequential_access(black_box(&mut data)));
+        let id = MEMORY_ACCESS.id("Sequential", &param);
+        group.bench_with_input(id.criterion(), &size, |b, &_size| {
+            probe::iter(b, &id, || sequential_access(black_box(&mut data)));
         });
 
         // Random Access
-        let mut rng = rng();
-        let mut indices: Vec<usize> = (0..data.len()).collect();
-        indices.shuffle(&mut rng);
+        let indices = workload::access_order(&mut rng, data.len());
 
-        group.bench_with_input(BenchmarkId::new("Random", size), &size, |b, &_size| {
-            b.iter(|| random_access(black_box(&mut data), black_box(&indices)));
+        let id = MEMORY_ACCESS.id("Random", &param);
+        group.bench_with_input(id.criterion(), &size, |b, &_size| {
+            probe::iter(b, &id, || {
+                random_access(black_box(&mut data), black_box(&indices))
+            });
         });
 
         // Stride Access with stride of 64 bytes
-        let stride = 64;
-        group.bench_with_input(BenchmarkId::new("Stride", size), &size, |b, &_size| {
-            b.iter(|| stride_access(black_box(&mut data), black_box(stride)));
+        let stride = MEMORY_ACCESS_STRIDE;
+        let id = MEMORY_ACCESS.id("Stride", &param);
+        group.bench_with_input(id.criterion(), &size, |b, &_size| {
+            probe::iter(b, &id, || {
+                stride_access(black_box(&mut data), black_box(stride))
+            });
         });
     }
 
-    group.finish();
+    probe::finish(group);
 }
 
-criterion_group!(memory, memory_access_benchmark);
+criterion_group! {
+    name = memory;
+    config = profile::criterion();
+    targets = memory_access_benchmark
+}
 criterion_main!(memory);
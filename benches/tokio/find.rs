@@ -1,246 +1,135 @@
-use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use dashmap::DashMap;
-use rand::{Rng, SeedableRng, seq::SliceRandom};
-use std::sync::Arc;
-use tokio::{runtime::Builder, sync::Mutex};
-use tokio_stream::{
-    StreamExt,
-    wrappers::{BroadcastStream, ReceiverStream, UnboundedReceiverStream},
+use criterion::{Criterion, criterion_group, criterion_main};
+use napkin_benches::{
+    affinity,
+    groups::{TOKIO_FIND, TOKIO_FIND_PAYLOAD},
+    hwinfo,
+    impls::tokio_find::{arc_mutex, broadcast, dash_map, mpsc, unbounded},
+    latency::{NoRecorder, Recorder},
+    probe, profile, workload,
 };
 
-pub type Data = Vec<u8>;
-
-async fn arc_mutex(
-    num_tasks: usize,
-    mut handles: Vec<tokio::task::JoinHandle<()>>,
-    values: Vec<(usize, Data)>,
-    mut indices_per_task: Vec<Vec<usize>>,
-) {
-    let counter = Arc::new(Mutex::new(values));
-
-    for _ in 0..num_tasks {
-        let counter = Arc::clone(&counter);
-        let indices = indices_per_task.pop().unwrap();
-        handles.push(tokio::spawn(async move {
-            // we search each index and we set the data zero
-            for i in indices {
-                let mut guard = counter.lock().await;
-                let (_, data) = guard.iter_mut().find(|(index, _)| *index == i).unwrap();
-                let len = data.len();
-                *data = vec![0; len];
-            }
-        }));
-    }
-
-    for handle in handles {
-        handle.await.unwrap();
-    }
-}
-
-async fn unbounded(
-    num_tasks: usize,
-    mut values: Vec<(usize, Data)>,
-    mut indices_per_task: Vec<Vec<usize>>,
-) {
-    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-    let mut rx = UnboundedReceiverStream::new(rx);
-
-    for _ in 0..num_tasks {
-        let tx = tx.clone();
-        let indices = indices_per_task.pop().unwrap();
-        tokio::spawn(async move {
-            for i in indices {
-                let (one_tx, one_rx) = tokio::sync::oneshot::channel();
-                tx.send((i, one_tx)).unwrap();
-                one_rx.await.unwrap();
-            }
-        });
-    }
-
-    drop(tx); // kill last sender
-
-    while let Some((i, one_tx)) = rx.next().await {
-        let (_, data) = values.iter_mut().find(|(index, _)| *index == i).unwrap();
-        let len = data.len();
-        *data = vec![0; len];
-        one_tx.send(()).unwrap();
-    }
-}
-
-async fn mpsc(
-    num_tasks: usize,
-    mut values: Vec<(usize, Data)>,
-    mut indices_per_task: Vec<Vec<usize>>,
-    capacity: usize,
-) {
-    let (tx, rx) = tokio::sync::mpsc::channel(capacity);
-    let mut rx = ReceiverStream::new(rx);
-
-    for _ in 0..num_tasks {
-        let tx = tx.clone();
-        let indices = indices_per_task.pop().unwrap();
-        tokio::spawn(async move {
-            for i in indices {
-                let (one_tx, one_rx) = tokio::sync::oneshot::channel();
-                tx.send((i, one_tx)).await.ok();
-                one_rx.await.unwrap();
-            }
-        });
-    }
+/// Every strategy of `tokio-find` at every point of its sweep.
+fn find(c: &mut Criterion) {
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
 
-    drop(tx); // kill last sender
+    let rt = affinity::runtime_builder().enable_all().build().unwrap();
+    let mut rng = workload::rng();
 
-    while let Some((i, one_tx)) = rx.next().await {
-        let (_, data) = values.iter_mut().find(|(index, _)| *index == i).unwrap();
-        let len = data.len();
-        *data = vec![0; len];
-        one_tx.send(()).unwrap();
-    }
-}
+    let num_tasks = TOKIO_FIND.sweep("tasks", &[10, 100, 1_000]);
+    let indices_per_task = TOKIO_FIND.sweep("indices", &[100, 1_000, 10_000]);
+    let channels_capacities = TOKIO_FIND.sweep("capacity", &[1, 100]);
 
-async fn broadcast(
-    num_tasks: usize,
-    mut values: Vec<(usize, Data)>,
-    mut indices_per_task: Vec<Vec<usize>>,
-) {
-    let (tx, rx) = tokio::sync::broadcast::channel(10_000_000);
-    let mut rx = BroadcastStream::new(rx);
-
-    for _ in 0..num_tasks {
-        let tx = tx.clone();
-        let indices = indices_per_task.pop().unwrap();
-        tokio::spawn(async move {
-            for i in indices {
-                let (one_tx, mut one_rx) = tokio::sync::mpsc::channel(1);
-                tx.send((i, one_tx)).ok();
-                one_rx.recv().await;
-            }
-        });
-    }
+    let mut group = c.benchmark_group(TOKIO_FIND.name);
 
-    drop(tx); // kill last sender
+    for &n in &num_tasks {
+        for &ind in &indices_per_task {
+            let param = TOKIO_FIND.params(&[n, ind]);
+            group.throughput(TOKIO_FIND.throughput(&param));
 
-    while let Some(Ok((i, one_tx))) = rx.next().await {
-        let (_, data) = values.iter_mut().find(|(index, _)| *index == i).unwrap();
-        let len = data.len();
-        *data = vec![0; len];
-        one_tx.send(()).await.unwrap();
-    }
-}
+            let random_ids = workload::random_keys(&mut rng, ind, 1_000_000);
 
-async fn dash_map(
-    num_tasks: usize,
-    mut handles: Vec<tokio::task::JoinHandle<()>>,
-    values: Vec<(usize, Data)>,
-    mut indices_per_task: Vec<Vec<usize>>,
-) {
-    let dash = Arc::new(
-        values
-            .iter()
-            .map(|(i, v)| (*i, v.clone()))
-            .collect::<DashMap<_, _>>(),
-    );
-
-    for _ in 0..num_tasks {
-        let indices = indices_per_task.pop().unwrap();
-        let dash = dash.clone();
-        handles.push(tokio::spawn(async move {
-            // we search each index and we set the data zero
-            for i in indices {
-                let mut data = dash.get_mut(&i).unwrap();
-                let len = data.len();
-                *data = vec![0; len];
-            }
-        }));
-    }
+            let keys = workload::shuffled(&mut rng, &random_ids);
+            // non-zero, so the zeroed payloads differ from the untouched ones
+            let data = workload::payload(TOKIO_FIND_PAYLOAD, 1);
+            let values = workload::entries(&keys, &data).collect::<Vec<_>>();
 
-    for handle in handles {
-        handle.await.unwrap();
-    }
-}
-
-// Benchmark using AtomicUsize
-fn find(c: &mut Criterion) {
-    let rt = Builder::new_current_thread().enable_all().build().unwrap();
-    let mut rng = rand::rngs::StdRng::seed_from_u64(64);
-
-    let num_tasks = [10, 100, 1_000];
-    let indices_per_task = [100, 1_000, 10_000];
-    let channels_capacities = [1, 100];
-
-    let mut group = c.benchmark_group("tokio-find");
-
-    for n in num_tasks.into_iter() {
-        for ind in indices_per_task.into_iter() {
-            let param = format!("{n}t{ind}");
-
-            let random_ids = (0..ind)
-                .map(|_| rng.random_range(0..1_000_000) as usize)
-                .collect::<Vec<_>>();
-
-            let mut values = random_ids.clone();
-            values.shuffle(&mut rng);
-            let values = values
-                .iter()
-                .map(|i| (*i, vec![0; 100]))
-                .collect::<Vec<_>>();
-
-            let indices_per_task = (0..n)
-                .map(|_| {
-                    let mut indices = random_ids.clone();
-                    indices.shuffle(&mut rng);
-                    indices
-                })
-                .collect::<Vec<_>>();
+            let indices_per_task = workload::indices_per_task(&mut rng, &random_ids, n);
 
+            let id = TOKIO_FIND.id("arc_mutex", &param);
             group.bench_with_input(
-                BenchmarkId::new("arc_mutex", param.clone()),
+                id.criterion(),
                 &(n, values.clone(), indices_per_task.clone()),
                 |b, (n, v, i)| {
-                    b.to_async(&rt)
-                        .iter(|| arc_mutex(*n, Vec::with_capacity(*n), v.clone(), i.clone()));
+                    probe::iter_async_recorded(
+                        b,
+                        &rt,
+                        &id,
+                        || {
+                            arc_mutex::<NoRecorder>(
+                                *n,
+                                Vec::with_capacity(*n),
+                                v.clone(),
+                                i.clone(),
+                            )
+                        },
+                        || arc_mutex::<Recorder>(*n, Vec::with_capacity(*n), v.clone(), i.clone()),
+                    );
                 },
             );
 
+            let id = TOKIO_FIND.id("dashmap", &param);
             group.bench_with_input(
-                BenchmarkId::new("dashmap", param.clone()),
+                id.criterion(),
                 &(n, values.clone(), indices_per_task.clone()),
                 |b, (n, v, i)| {
-                    b.to_async(&rt)
-                        .iter(|| dash_map(*n, Vec::with_capacity(*n), v.clone(), i.clone()));
+                    probe::iter_async_recorded(
+                        b,
+                        &rt,
+                        &id,
+                        || dash_map::<NoRecorder>(*n, Vec::with_capacity(*n), v.clone(), i.clone()),
+                        || dash_map::<Recorder>(*n, Vec::with_capacity(*n), v.clone(), i.clone()),
+                    );
                 },
             );
 
+            let id = TOKIO_FIND.id("unbounded_ch", &param);
             group.bench_with_input(
-                BenchmarkId::new("unbounded_ch", param.clone()),
+                id.criterion(),
                 &(n, values.clone(), indices_per_task.clone()),
                 |b, (n, v, i)| {
-                    b.to_async(&rt).iter(|| unbounded(*n, v.clone(), i.clone()));
+                    probe::iter_async_recorded(
+                        b,
+                        &rt,
+                        &id,
+                        || unbounded::<NoRecorder>(*n, v.clone(), i.clone()),
+                        || unbounded::<Recorder>(*n, v.clone(), i.clone()),
+                    );
                 },
             );
 
+            let id = TOKIO_FIND.id("broadcast_ch", &param);
             group.bench_with_input(
-                BenchmarkId::new("broadcast_ch", param.clone()),
+                id.criterion(),
                 &(n, values.clone(), indices_per_task.clone()),
                 |b, (n, v, i)| {
-                    b.to_async(&rt).iter(|| broadcast(*n, v.clone(), i.clone()));
+                    probe::iter_async_recorded(
+                        b,
+                        &rt,
+                        &id,
+                        || broadcast::<NoRecorder>(*n, v.clone(), i.clone()),
+                        || broadcast::<Recorder>(*n, v.clone(), i.clone()),
+                    );
                 },
             );
 
-            for cap in channels_capacities.into_iter() {
-                let param = format!("{n}t{ind}c{cap}");
+            for &cap in &channels_capacities {
+                let param = TOKIO_FIND.params(&[n, ind, cap]);
 
+                let id = TOKIO_FIND.id("mpsc_ch", &param);
                 group.bench_with_input(
-                    BenchmarkId::new("mpsc_ch", param.clone()),
+                    id.criterion(),
                     &(n, values.clone(), indices_per_task.clone()),
                     |b, (n, v, i)| {
-                        b.to_async(&rt).iter(|| mpsc(*n, v.clone(), i.clone(), cap));
+                        probe::iter_async_recorded(
+                            b,
+                            &rt,
+                            &id,
+                            || mpsc::<NoRecorder>(*n, v.clone(), i.clone(), cap),
+                            || mpsc::<Recorder>(*n, v.clone(), i.clone(), cap),
+                        );
                     },
                 );
             }
         }
     }
+
+    probe::finish(group);
 }
 
-criterion_group!(benches, find);
+criterion_group! {
+    name = benches;
+    config = profile::criterion();
+    targets = find
+}
 criterion_main!(benches);
@@ -1,206 +1,95 @@
-use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use std::sync::{
-    Arc,
-    atomic::{AtomicUsize, Ordering},
+use criterion::{Criterion, criterion_group, criterion_main};
+use napkin_benches::{
+    affinity,
+    groups::TOKIO_INCREMENT,
+    hwinfo,
+    impls::tokio_increment::{arc_mutex, atomic_usize, broadcast, mpsc, unbounded},
+    latency::{NoRecorder, Recorder},
+    probe, profile,
 };
-use tokio::{runtime::Builder, sync::Mutex};
-use tokio_stream::{
-    StreamExt,
-    wrappers::{BroadcastStream, ReceiverStream, UnboundedReceiverStream},
-};
-
-async fn atomic_usize(
-    num_tasks: usize,
-    increments_per_task: usize,
-    mut handles: Vec<tokio::task::JoinHandle<()>>,
-) {
-    let counter = Arc::new(AtomicUsize::new(0));
-
-    for _ in 0..num_tasks {
-        let counter = counter.clone();
-        handles.push(tokio::spawn(async move {
-            for _ in 0..increments_per_task {
-                counter.fetch_add(1, Ordering::Relaxed);
-            }
-        }));
-    }
-
-    for handle in handles {
-        handle.await.unwrap();
-    }
-
-    assert_eq!(
-        counter.load(Ordering::Relaxed),
-        num_tasks * increments_per_task
-    );
-}
-
-async fn arc_mutex(
-    num_tasks: usize,
-    increments_per_task: usize,
-    mut handles: Vec<tokio::task::JoinHandle<()>>,
-) {
-    let counter = Arc::new(Mutex::new(0usize));
-
-    for _ in 0..num_tasks {
-        let counter = Arc::clone(&counter);
-        handles.push(tokio::spawn(async move {
-            for _ in 0..increments_per_task {
-                let mut lock = counter.lock().await;
-                *lock += 1;
-            }
-        }));
-    }
-
-    for handle in handles {
-        handle.await.unwrap();
-    }
-
-    assert_eq!(*counter.lock().await, num_tasks * increments_per_task);
-}
-
-async fn unbounded(num_tasks: usize, increments_per_task: usize) {
-    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-    let mut rx = UnboundedReceiverStream::new(rx);
-    let mut counter = 0;
-
-    for _ in 0..num_tasks {
-        let tx = tx.clone();
-        tokio::spawn(async move {
-            for _ in 0..increments_per_task {
-                tx.send(()).unwrap();
-            }
-        });
-    }
-    while (rx.next().await).is_some() {
-        counter += 1;
-        if counter == num_tasks * increments_per_task {
-            break;
-        }
-    }
-
-    assert_eq!(counter, num_tasks * increments_per_task);
-}
-
-async fn mpsc(num_tasks: usize, increments_per_task: usize, capacity: usize) {
-    let (tx, rx) = tokio::sync::mpsc::channel(capacity);
-    let mut rx = ReceiverStream::new(rx);
-    let mut counter = 0;
-
-    for _ in 0..num_tasks {
-        let tx = tx.clone();
-        tokio::spawn(async move {
-            for _ in 0..increments_per_task {
-                loop {
-                    if tx.send(()).await.is_ok() {
-                        break;
-                    }
-                }
-            }
-        });
-    }
 
-    while (rx.next().await).is_some() {
-        counter += 1;
-        if counter == num_tasks * increments_per_task {
-            break;
-        }
-    }
-
-    assert_eq!(counter, num_tasks * increments_per_task);
-}
-
-async fn broadcast(num_tasks: usize, increments_per_task: usize) {
-    let (tx, rx) = tokio::sync::broadcast::channel(10_000_000);
-    let mut rx = BroadcastStream::new(rx);
-    let mut counter = 0;
-
-    for _ in 0..num_tasks {
-        let tx = tx.clone();
-        tokio::spawn(async move {
-            for _ in 0..increments_per_task {
-                loop {
-                    if tx.send(()).is_ok() {
-                        break;
-                    }
-                }
-            }
-        });
-    }
-
-    while (rx.next().await).is_some() {
-        counter += 1;
-        if counter == num_tasks * increments_per_task {
-            break;
-        }
-    }
-
-    assert_eq!(counter, num_tasks * increments_per_task);
-}
-
-// Benchmark using AtomicUsize
+/// Every counter of `tokio-increment` at every point of its sweep.
 fn increment(c: &mut Criterion) {
-    let rt = Builder::new_current_thread().enable_all().build().unwrap();
-
-    let num_tasks = [4, 8, 16];
-    let increments_per_task = [10_000, 100_000, 1_000_000];
-
-    let channels_capacities = [1, 100];
-
-    let mut group = c.benchmark_group("tokio-increment");
-
-    for n in num_tasks.into_iter() {
-        for inc in increments_per_task.into_iter() {
-            let param = format!("{n}t{inc}");
-
-            group.bench_with_input(
-                BenchmarkId::new("atomic_usize", param.clone()),
-                &(n, inc),
-                |b, (n, inc)| {
-                    b.to_async(&rt)
-                        .iter(|| atomic_usize(*n, *inc, Vec::with_capacity(*n)));
-                },
-            );
-
-            group.bench_with_input(
-                BenchmarkId::new("arc_mutex", param.clone()),
-                &(n, inc),
-                |b, (n, inc)| {
-                    b.to_async(&rt)
-                        .iter(|| arc_mutex(*n, *inc, Vec::with_capacity(*n)));
-                },
-            );
-
-            group.bench_with_input(
-                BenchmarkId::new("unbounded_ch", param.clone()),
-                &(n, inc),
-                |b, (n, inc)| {
-                    b.to_async(&rt).iter(|| unbounded(*n, *inc));
-                },
-            );
-
-            group.bench_with_input(
-                BenchmarkId::new("broadcast_ch", param),
-                &(n, inc),
-                |b, (n, inc)| {
-                    b.to_async(&rt).iter(|| broadcast(*n, *inc));
-                },
-            );
-
-            for cap in channels_capacities.into_iter() {
-                let param = format!("{n}t{inc}c{cap}");
-
-                group.bench_with_input(
-                    BenchmarkId::new("mpsc_ch", param.clone()),
-                    &(n, inc, cap),
-                    |b, (n, inc, cap)| {
-                        b.to_async(&rt).iter(|| mpsc(*n, *inc, *cap));
-                    },
+    hwinfo::record().expect("failed to record the hardware profile");
+    affinity::pin_bench_thread().expect("failed to pin the benchmark thread");
+
+    let rt = affinity::runtime_builder().enable_all().build().unwrap();
+
+    let num_tasks = TOKIO_INCREMENT.sweep("tasks", &[4, 8, 16]);
+    let increments_per_task = TOKIO_INCREMENT.sweep("increments", &[10_000, 100_000, 1_000_000]);
+
+    let channels_capacities = TOKIO_INCREMENT.sweep("capacity", &[1, 100]);
+
+    let mut group = c.benchmark_group(TOKIO_INCREMENT.name);
+
+    for &n in &num_tasks {
+        for &inc in &increments_per_task {
+            let param = TOKIO_INCREMENT.params(&[n, inc]);
+            group.throughput(TOKIO_INCREMENT.throughput(&param));
+
+            let id = TOKIO_INCREMENT.id("atomic_usize", &param);
+            group.bench_with_input(id.criterion(), &(n, inc), |b, (n, inc)| {
+                probe::iter_async(b, &rt, &id, || {
+                    atomic_usize(*n, *inc, Vec::with_capacity(*n))
+                });
+            });
+
+            let id = TOKIO_INCREMENT.id("arc_mutex", &param);
+            group.bench_with_input(id.criterion(), &(n, inc), |b, (n, inc)| {
+                probe::iter_async_recorded(
+                    b,
+                    &rt,
+                    &id,
+                    || arc_mutex::<NoRecorder>(*n, *inc, Vec::with_capacity(*n)),
+                    || arc_mutex::<Recorder>(*n, *inc, Vec::with_capacity(*n)),
+                );
+            });
+
+            let id = TOKIO_INCREMENT.id("unbounded_ch", &param);
+            group.bench_with_input(id.criterion(), &(n, inc), |b, (n, inc)| {
+                probe::iter_async_recorded(
+                    b,
+                    &rt,
+                    &id,
+                    || unbounded::<NoRecorder>(*n, *inc),
+                    || unbounded::<Recorder>(*n, *inc),
                 );
+            });
+
+            let id = TOKIO_INCREMENT.id("broadcast_ch", &param);
+            group.bench_with_input(id.criterion(), &(n, inc), |b, (n, inc)| {
+                probe::iter_async_recorded(
+                    b,
+                    &rt,
+                    &id,
+                    || broadcast::<NoRecorder>(*n, *inc),
+                    || broadcast::<Recorder>(*n, *inc),
+                );
+            });
+
+            for &cap in &channels_capacities {
+                let param = TOKIO_INCREMENT.params(&[n, inc, cap]);
+
+                let id = TOKIO_INCREMENT.id("mpsc_ch", &param);
+                group.bench_with_input(id.criterion(), &(n, inc, cap), |b, (n, inc, cap)| {
+                    probe::iter_async_recorded(
+                        b,
+                        &rt,
+                        &id,
+                        || mpsc::<NoRecorder>(*n, *inc, *cap),
+                        || mpsc::<Recorder>(*n, *inc, *cap),
+                    );
+                });
             }
         }
     }
+
+    probe::finish(group);
 }
 
-criterion_group!(benches, increment);
+criterion_group! {
+    name = benches;
+    config = profile::criterion();
+    targets = increment
+}
 criterion_main!(benches);
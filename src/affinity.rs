@@ -0,0 +1,205 @@
+//! CPU affinity of the benchmark thread and the tokio worker threads.
+//!
+//! Bench targets read two CPU lists from the environment, in the kernel's
+//! format (`0-3,8`): [`BENCH_CPUS_VAR`] pins the thread Criterion measures on,
+//! and [`WORKER_CPUS_VAR`] switches the async groups to a multi-threaded
+//! runtime whose workers are pinned to that set. A [`Placement`] derives both
+//! lists from the machine's topology. Pinning is only supported on Linux.
+//!
+//! A list that does not parse panics, as a typo in the parameter matrix does,
+//! rather than leaving the run unpinned while it is recorded as pinned.
+
+use crate::hwinfo::HardwareProfile;
+use std::{env, fmt, io, str::FromStr};
+use tokio::runtime::Builder;
+
+/// CPUs the benchmark thread is pinned to.
+pub const BENCH_CPUS_VAR: &str = "NAPKIN_BENCH_CPUS";
+/// CPUs the tokio worker threads are pinned to.
+pub const WORKER_CPUS_VAR: &str = "NAPKIN_WORKER_CPUS";
+
+/// Where the tokio workers run relative to the benchmark thread, itself
+/// pinned to the first CPU of the first core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// On the benchmark thread's CPU.
+    SameCore,
+    /// On the other hardware threads of the benchmark thread's core.
+    SmtSibling,
+    /// On the other cores sharing the benchmark thread's cache cluster.
+    SameCluster,
+    /// On the cores of another cache cluster.
+    OtherCluster,
+}
+
+impl Placement {
+    pub const ALL: [Placement; 4] = [
+        Placement::SameCore,
+        Placement::SmtSibling,
+        Placement::SameCluster,
+        Placement::OtherCluster,
+    ];
+
+    /// The CPU sets of this placement on `profile`, or `None` when the
+    /// topology has no such CPUs, e.g. SMT siblings on a machine without SMT.
+    pub fn cpus(&self, profile: &HardwareProfile) -> Option<CpuSets> {
+        let core = profile.cores.first()?;
+        let anchor = *core.first()?;
+        let cluster = profile
+            .clusters
+            .iter()
+            .find(|cluster| cluster.contains(&anchor))?;
+
+        let workers: Vec<usize> = match self {
+            Placement::SameCore => vec![anchor],
+            Placement::SmtSibling => core.iter().copied().filter(|&c| c != anchor).collect(),
+            Placement::SameCluster => cluster
+                .iter()
+                .copied()
+                .filter(|c| !core.contains(c))
+                .collect(),
+            Placement::OtherCluster => profile
+                .clusters
+                .iter()
+                .find(|other| !other.contains(&anchor))?
+                .clone(),
+        };
+        (!workers.is_empty()).then(|| CpuSets {
+            bench: vec![anchor],
+            workers,
+        })
+    }
+}
+
+/// CPUs to pin the benchmark thread and the tokio workers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuSets {
+    pub bench: Vec<usize>,
+    pub workers: Vec<usize>,
+}
+
+impl CpuSets {
+    /// The environment variables passing these sets to a bench target.
+    pub fn env(&self) -> [(&'static str, String); 2] {
+        [
+            (BENCH_CPUS_VAR, format_cpu_list(&self.bench)),
+            (WORKER_CPUS_VAR, format_cpu_list(&self.workers)),
+        ]
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Placement::SameCore => "same-core",
+            Placement::SmtSibling => "smt-sibling",
+            Placement::SameCluster => "same-cluster",
+            Placement::OtherCluster => "other-cluster",
+        })
+    }
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|placement| placement.to_string() == text)
+            .ok_or_else(|| {
+                let all = Self::ALL.map(|p| p.to_string()).join(", ");
+                format!("unknown placement `{text}`, expected one of {all}")
+            })
+    }
+}
+
+/// Formats CPUs as a list [`parse_cpus`] reads back.
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    cpus.iter()
+        .map(|cpu| cpu.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parses a CPU list like [`crate::hwinfo::parse_cpu_list`], but rejects empty
+/// lists and anything that is not a CPU or a range of CPUs.
+pub fn parse_cpus(list: &str) -> Result<Vec<usize>, String> {
+    let invalid = |part: &str| format!("invalid CPU list `{list}`: `{part}` is not a CPU or range");
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',') {
+        let cpu = |n: &str| n.parse::<usize>().map_err(|_| invalid(part));
+        match part.split_once('-') {
+            Some((lo, hi)) if cpu(lo)? <= cpu(hi)? => cpus.extend(cpu(lo)?..=cpu(hi)?),
+            Some(_) => return Err(invalid(part)),
+            None => cpus.push(cpu(part)?),
+        }
+    }
+    Ok(cpus)
+}
+
+/// The CPUs listed in `var`, `None` when it is unset.
+///
+/// # Panics
+///
+/// Panics if the list does not parse.
+fn cpus_from_env(var: &str) -> Option<Vec<usize>> {
+    let list = env::var(var).ok()?;
+    Some(parse_cpus(&list).unwrap_or_else(|e| panic!("{var}: {e}")))
+}
+
+/// Pins the calling thread to the CPUs in [`BENCH_CPUS_VAR`], if set. Every
+/// bench target calls this before registering its groups.
+pub fn pin_bench_thread() -> io::Result<()> {
+    match cpus_from_env(BENCH_CPUS_VAR) {
+        Some(cpus) => pin_current_thread(&cpus),
+        None => Ok(()),
+    }
+}
+
+/// A builder for the runtime of the async groups: current-thread by default,
+/// or multi-threaded with one worker per CPU in [`WORKER_CPUS_VAR`], each
+/// pinned to that set.
+pub fn runtime_builder() -> Builder {
+    let Some(cpus) = cpus_from_env(WORKER_CPUS_VAR) else {
+        return Builder::new_current_thread();
+    };
+    let mut builder = Builder::new_multi_thread();
+    builder.worker_threads(cpus.len()).on_thread_start(move || {
+        pin_current_thread(&cpus).expect("failed to pin a tokio worker thread")
+    });
+    builder
+}
+
+/// Restricts the calling thread to `cpus`.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cpus: &[usize]) -> io::Result<()> {
+    // `CPU_SET` panics past the end of the set
+    if let Some(cpu) = cpus.iter().find(|&&cpu| cpu >= libc::CPU_SETSIZE as usize) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "CPU {cpu} is past the {} CPUs a set holds",
+                libc::CPU_SETSIZE
+            ),
+        ));
+    }
+    let mut set = unsafe { std::mem::zeroed::<libc::cpu_set_t>() };
+    for &cpu in cpus {
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    // pid 0 is the calling thread
+    let ret = unsafe { libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set) };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Restricts the calling thread to `cpus`.
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_cpus: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "CPU pinning is only supported on Linux",
+    ))
+}
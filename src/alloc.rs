@@ -0,0 +1,110 @@
+//! Allocation-counting global allocator.
+//!
+//! Built with the `alloc-count` feature, the library installs
+//! [`CountingAllocator`] as the `#[global_allocator]` of every binary linking
+//! it, and each benchmark records the allocations, frees and bytes allocated
+//! per iteration next to its timings. Without the feature nothing is counted.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    ops::{Add, Sub},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Whether this build counts allocations.
+pub const ENABLED: bool = cfg!(feature = "alloc-count");
+
+/// Sidecar kind holding the [`AllocStats`] of every benchmark.
+pub const SIDECAR: &str = "alloc";
+
+#[cfg(feature = "alloc-count")]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+static ALLOCS: AtomicU64 = AtomicU64::new(0);
+static FREES: AtomicU64 = AtomicU64::new(0);
+static BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting every call. A `realloc` counts as one
+/// allocation of the new size and one free.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        FREES.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        FREES.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Allocator activity, either cumulative or per iteration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AllocStats {
+    pub allocs: f64,
+    pub frees: f64,
+    pub bytes: f64,
+}
+
+impl AllocStats {
+    /// Counts since the process started, on every thread.
+    pub fn now() -> Self {
+        Self {
+            allocs: ALLOCS.load(Ordering::Relaxed) as f64,
+            frees: FREES.load(Ordering::Relaxed) as f64,
+            bytes: BYTES.load(Ordering::Relaxed) as f64,
+        }
+    }
+
+    /// Averages the counts over `iterations`.
+    pub fn per(self, iterations: u64) -> Self {
+        let n = iterations.max(1) as f64;
+        Self {
+            allocs: self.allocs / n,
+            frees: self.frees / n,
+            bytes: self.bytes / n,
+        }
+    }
+}
+
+impl Add for AllocStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            allocs: self.allocs + rhs.allocs,
+            frees: self.frees + rhs.frees,
+            bytes: self.bytes + rhs.bytes,
+        }
+    }
+}
+
+impl Sub for AllocStats {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            allocs: self.allocs - rhs.allocs,
+            frees: self.frees - rhs.frees,
+            bytes: self.bytes - rhs.bytes,
+        }
+    }
+}
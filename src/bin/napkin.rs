@@ -0,0 +1,658 @@
+//! `napkin`: lists and runs the benchmarks of every bench target, and saves
+//! what a run measured as one consolidated result set.
+//!
+//! ```text
+//! cargo run --release --bin napkin -- list 'tokio-*'
+//! cargo run --release --bin napkin -- run 'collections-find/*Map Find/*'
+//! ```
+
+use clap::{Args, Parser, Subcommand};
+use napkin_benches::{
+    affinity::{CpuSets, Placement},
+    compare,
+    config::{self, ConfigError, Matrix},
+    crossover::{self, Lookups, Point},
+    export,
+    gate::{self, Gate, Verdict},
+    glob::{self, Pattern},
+    groups::{self, COLLECTIONS_FIND, Group},
+    hwinfo::{self, HardwareProfile},
+    paths,
+    profile::{self, Profile},
+    report,
+    results::{self, BenchResult, Run},
+    workload::{KeyDistribution, KeyPosition},
+};
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Parser)]
+#[command(about = "Run napkin-benches groups and collect their results")]
+struct Cli {
+    #[command(subcommand)]
+    command: Cmd,
+}
+
+/// What the bench targets sweep: the measurement profile and the parameter
+/// matrix, see `napkin_benches::profile` and `napkin_benches::config`.
+#[derive(Args)]
+struct SweepArgs {
+    /// `quick` for a thinned grid with few short samples, `full` (default)
+    /// for publishable numbers.
+    #[arg(long)]
+    profile: Option<Profile>,
+    /// TOML parameter matrix to read instead of `napkin.toml`.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Sweep these values along one dimension, e.g.
+    /// `tokio-increment.tasks=4,8`. Repeatable; wins over the TOML file.
+    #[arg(long = "set", value_name = "GROUP.DIM=V1,V2")]
+    overrides: Vec<String>,
+}
+
+impl SweepArgs {
+    fn apply(&self, command: &mut Command) -> Result<(), ConfigError> {
+        if let Some(profile) = self.profile {
+            command.env(profile::PROFILE_VAR, profile.to_string());
+        }
+        if let Some(path) = &self.config {
+            command.env(config::CONFIG_VAR, path);
+        }
+        if !self.overrides.is_empty() {
+            let overrides = self.overrides.join(";");
+            Matrix::from_overrides("--set", &overrides)?;
+            command.env(config::SET_VAR, overrides);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// List every benchmark id (`group/function/value`) matching the patterns.
+    List {
+        /// Glob patterns over benchmark ids; all benchmarks when omitted.
+        patterns: Vec<String>,
+        /// Print each parameter split into its named dimensions.
+        #[arg(long)]
+        params: bool,
+        #[command(flatten)]
+        sweep: SweepArgs,
+    },
+    /// Run the benchmarks matching the patterns and save them as one run.
+    Run {
+        /// Glob patterns over benchmark ids; all benchmarks when omitted.
+        patterns: Vec<String>,
+        /// Cargo features to build the bench targets with, e.g. `alloc-count`
+        /// to count allocations per iteration.
+        #[arg(long)]
+        features: Option<String>,
+        /// Pin the benchmark thread and run the async groups on tokio workers
+        /// placed relative to it: same-core, smt-sibling, same-cluster or
+        /// other-cluster. Repeat to sweep placements, saving one run each.
+        #[arg(long = "placement")]
+        placements: Vec<Placement>,
+        #[command(flatten)]
+        sweep: SweepArgs,
+        /// Extra arguments passed to every Criterion target after `--`.
+        #[arg(last = true)]
+        criterion_args: Vec<String>,
+    },
+    /// Export results as `results.csv` and `results.json`, one row per
+    /// benchmark with its parameter split into dimensions.
+    Export {
+        /// Glob patterns over benchmark ids; all benchmarks when omitted.
+        patterns: Vec<String>,
+        /// Export a saved run (path, `<fingerprint>/<id>` or fingerprint)
+        /// instead of Criterion's latest measurements.
+        #[arg(long)]
+        run: Option<String>,
+        /// Output directory.
+        #[arg(long, default_value = "target/napkin/export")]
+        out: PathBuf,
+    },
+    /// Render Markdown tables comparing the implementations at every
+    /// parameter point, with their ratio to the fastest one.
+    Report {
+        /// Glob patterns over benchmark ids; all benchmarks when omitted.
+        patterns: Vec<String>,
+        /// Report a saved run (path, `<fingerprint>/<id>` or fingerprint)
+        /// instead of Criterion's latest measurements.
+        #[arg(long)]
+        run: Option<String>,
+        /// Rewrite the generated results section of this Markdown file
+        /// (usually `Readme.md`) instead of printing the report.
+        #[arg(long)]
+        readme: Option<PathBuf>,
+    },
+    /// List saved runs by machine fingerprint.
+    Runs,
+    /// Compare two saved runs side by side, e.g. from two machines.
+    Compare {
+        /// Run A: path, `<fingerprint>/<id>` or fingerprint (latest run).
+        a: String,
+        /// Run B: path, `<fingerprint>/<id>` or fingerprint (latest run).
+        b: String,
+        /// Glob patterns over benchmark ids; all benchmarks when omitted.
+        patterns: Vec<String>,
+    },
+    /// Compare Criterion's latest measurements against a saved baseline
+    /// (`--save-baseline NAME`) and fail when any benchmark regressed.
+    Gate {
+        /// Name of the Criterion baseline.
+        #[arg(long)]
+        baseline: String,
+        /// Glob patterns over benchmark ids; all benchmarks when omitted.
+        patterns: Vec<String>,
+        /// Relative slowdown always tolerated.
+        #[arg(long, default_value_t = Gate::default().threshold)]
+        threshold: f64,
+        /// Relative standard deviations of noise tolerated on top of the
+        /// threshold.
+        #[arg(long, default_value_t = Gate::default().noise_factor)]
+        noise_factor: f64,
+    },
+    /// Bisect over `collections-find` lengths for the N where the faster of
+    /// two implementations changes, at each payload size.
+    Crossover {
+        /// Function name, e.g. `SmallVec<8> Find`.
+        a: String,
+        /// Function name, e.g. `FxHashMap Find`.
+        b: String,
+        /// Payload sizes to search at, in KiB. Repeatable; defaults to the
+        /// sizes `collections-find` sweeps on this machine.
+        #[arg(long = "payload-kb")]
+        payload_kb: Vec<usize>,
+        /// Shortest length searched.
+        #[arg(long, default_value_t = 1)]
+        min: usize,
+        /// Longest length searched, lowered to what fits in memory.
+        #[arg(long, default_value_t = 4096)]
+        max: usize,
+        /// Lengths measured between the two ends at most.
+        #[arg(long, default_value_t = 12)]
+        steps: usize,
+        #[command(flatten)]
+        lookups: LookupArgs,
+        /// `quick` for few short samples per length, `full` (default) for
+        /// Criterion's settings.
+        #[arg(long)]
+        profile: Option<Profile>,
+    },
+}
+
+/// Which keys and lookups `collections-find` times in a crossover search.
+#[derive(Args)]
+struct LookupArgs {
+    /// Key distribution: dense, sparse, clustered, power-of-two or
+    /// fx-collisions.
+    #[arg(long, default_value_t = KeyDistribution::Dense)]
+    keys: KeyDistribution,
+    /// Share of the lookups that hit, in percent.
+    #[arg(long, default_value_t = 100)]
+    hit_pct: usize,
+    /// Where the key hits target sits: front, middle, back or absent.
+    #[arg(long, default_value_t = KeyPosition::Middle)]
+    position: KeyPosition,
+}
+
+impl LookupArgs {
+    fn lookups(&self) -> Lookups {
+        Lookups {
+            keys: self.keys,
+            hit_pct: self.hit_pct,
+            position: self.position,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let outcome = match cli.command {
+        Cmd::List {
+            patterns,
+            params,
+            sweep,
+        } => list(&parse_patterns(&patterns), params, &sweep),
+        Cmd::Run {
+            patterns,
+            features,
+            placements,
+            sweep,
+            criterion_args,
+        } => run(
+            &patterns,
+            features.as_deref(),
+            &placements,
+            &sweep,
+            &criterion_args,
+        ),
+        Cmd::Export { patterns, run, out } => export(&parse_patterns(&patterns), run, &out),
+        Cmd::Report {
+            patterns,
+            run,
+            readme,
+        } => report(&parse_patterns(&patterns), run, readme),
+        Cmd::Runs => runs(),
+        Cmd::Compare { a, b, patterns } => compare(&a, &b, &parse_patterns(&patterns)),
+        Cmd::Gate {
+            baseline,
+            patterns,
+            threshold,
+            noise_factor,
+        } => gate(
+            &baseline,
+            &parse_patterns(&patterns),
+            Gate {
+                threshold,
+                noise_factor,
+            },
+        ),
+        Cmd::Crossover {
+            a,
+            b,
+            payload_kb,
+            min,
+            max,
+            steps,
+            lookups,
+            profile,
+        } => crossover(&a, &b, payload_kb, (min, max), steps, &lookups, profile),
+    };
+    match outcome {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("napkin: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn list(patterns: &[Pattern], params: bool, sweep: &SweepArgs) -> Result<(), Box<dyn Error>> {
+    for group in selected_groups(patterns) {
+        let mut command = cargo_bench(group, None, &["--list".to_string()]);
+        sweep.apply(&mut command)?;
+        let output = command.stderr(Stdio::inherit()).output()?;
+        if !output.status.success() {
+            return Err(format!("listing `{}` failed: {}", group.target, output.status).into());
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.strip_suffix(": benchmark"))
+            .filter(|id| is_selected(patterns, id))
+            .for_each(|id| {
+                if params {
+                    println!("{id}\t{}", describe_params(group, id));
+                } else {
+                    println!("{id}");
+                }
+            });
+    }
+    Ok(())
+}
+
+fn run(
+    patterns: &[String],
+    features: Option<&str>,
+    placements: &[Placement],
+    sweep: &SweepArgs,
+    criterion_args: &[String],
+) -> Result<(), Box<dyn Error>> {
+    if placements.is_empty() {
+        return run_once(patterns, features, None, sweep, criterion_args);
+    }
+    let profile = HardwareProfile::detect();
+    for &placement in placements {
+        match placement.cpus(&profile) {
+            Some(cpus) => run_once(
+                patterns,
+                features,
+                Some((placement, cpus)),
+                sweep,
+                criterion_args,
+            )?,
+            None => eprintln!("napkin: skipping placement `{placement}`, no such CPUs here"),
+        }
+    }
+    Ok(())
+}
+
+fn run_once(
+    patterns: &[String],
+    features: Option<&str>,
+    pinning: Option<(Placement, CpuSets)>,
+    sweep: &SweepArgs,
+    criterion_args: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let started = SystemTime::now();
+    let parsed = parse_patterns(patterns);
+
+    let mut args = Vec::new();
+    if !parsed.is_empty() {
+        args.push(glob::filter_regex(&parsed));
+    }
+    args.extend_from_slice(criterion_args);
+
+    for group in selected_groups(&parsed) {
+        let mut command = cargo_bench(group, features, &args);
+        if let Some((_, cpus)) = &pinning {
+            command.envs(cpus.env());
+        }
+        sweep.apply(&mut command)?;
+        let status = command.status()?;
+        if !status.success() {
+            return Err(format!("running `{}` failed: {status}", group.target).into());
+        }
+    }
+
+    let results = results::collect_since(&paths::criterion_home(), started)?
+        .into_iter()
+        .filter(|result| is_selected(&parsed, &result.id))
+        .collect::<Vec<_>>();
+
+    let started = started.duration_since(UNIX_EPOCH)?;
+    // runs started within the same second must not share a directory
+    let stamp = format!("{}.{:09}", started.as_secs(), started.subsec_nanos());
+    let placement = pinning.map(|(placement, _)| placement.to_string());
+    let run = Run {
+        id: match &placement {
+            Some(placement) => format!("{stamp}-{placement}"),
+            None => stamp,
+        },
+        started: started.as_secs(),
+        patterns: patterns.to_vec(),
+        profile: sweep.profile.map(|profile| profile.to_string()),
+        placement,
+        hardware: HardwareProfile::detect(),
+        results,
+    };
+    let path = run.save(&Run::dir())?;
+    export::write_all(&export::rows(&run.results), path.parent().unwrap())?;
+    println!(
+        "saved {} results of run {} to {}",
+        run.results.len(),
+        run.id,
+        path.display()
+    );
+    Ok(())
+}
+
+/// `name=value` pairs of the parameter of `id`, e.g. `tasks=4 increments=10000`.
+fn describe_params(group: &Group, id: &str) -> String {
+    let Some((_, value)) = id.rsplit_once('/') else {
+        return String::new();
+    };
+    match group.parse(value) {
+        Ok(params) => params
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn export(patterns: &[Pattern], run: Option<String>, out: &Path) -> Result<(), Box<dyn Error>> {
+    let (results, _) = load_results(patterns, run)?;
+    export::write_all(&export::rows(&results), out)?;
+    println!("exported {} results to {}", results.len(), out.display());
+    Ok(())
+}
+
+fn report(
+    patterns: &[Pattern],
+    run: Option<String>,
+    readme: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let (results, hardware) = load_results(patterns, run)?;
+    let markdown = report::markdown(&export::rows(&results), hardware.as_ref());
+    match readme {
+        Some(path) => {
+            let current = fs::read_to_string(&path)?;
+            fs::write(&path, report::replace_section(&current, &markdown))?;
+            println!("wrote {} results to {}", results.len(), path.display());
+        }
+        None => print!("{markdown}"),
+    }
+    Ok(())
+}
+
+fn runs() -> Result<(), Box<dyn Error>> {
+    let dir = Run::dir();
+    for (machine, id) in Run::list(&dir)? {
+        let run = Run::load(&dir.join(&machine).join(&id))?;
+        println!(
+            "{machine}/{id}\t{} results\t{}",
+            run.results.len(),
+            run.patterns.join(" ")
+        );
+    }
+    Ok(())
+}
+
+fn compare(a: &str, b: &str, patterns: &[Pattern]) -> Result<(), Box<dyn Error>> {
+    let dir = Run::dir();
+    let mut a = Run::load(&Run::resolve(&dir, a)?)?;
+    let mut b = Run::load(&Run::resolve(&dir, b)?)?;
+    a.results.retain(|result| is_selected(patterns, &result.id));
+    b.results.retain(|result| is_selected(patterns, &result.id));
+
+    let comparison = compare::compare(&a.results, &b.results);
+    print!("{}", compare::markdown(&a, &b, &comparison));
+    Ok(())
+}
+
+fn gate(baseline: &str, patterns: &[Pattern], gate: Gate) -> Result<(), Box<dyn Error>> {
+    let home = paths::criterion_home();
+    let selected = |results: Vec<BenchResult>| {
+        results
+            .into_iter()
+            .filter(|result| is_selected(patterns, &result.id))
+            .collect::<Vec<_>>()
+    };
+    let base = selected(results::collect(&home, baseline)?);
+    if base.is_empty() {
+        return Err(format!("no benchmark saved under baseline `{baseline}`").into());
+    }
+    let current = selected(results::collect(&home, results::NEW)?);
+
+    let checks = gate.check(&base, &current);
+    print!("{}", gate::markdown(&checks));
+
+    let count = |verdict| {
+        checks
+            .iter()
+            .filter(|check| check.verdict == verdict)
+            .count()
+    };
+    let (regressed, missing) = (count(Verdict::Regressed), count(Verdict::Missing));
+    if regressed > 0 || missing > 0 {
+        return Err(format!(
+            "{regressed} of {} benchmarks regressed, {missing} were not measured",
+            checks.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn crossover(
+    a: &str,
+    b: &str,
+    payload_kb: Vec<usize>,
+    (min, max): (usize, usize),
+    steps: usize,
+    lookups: &LookupArgs,
+    profile: Option<Profile>,
+) -> Result<(), Box<dyn Error>> {
+    if min == 0 || min >= max {
+        return Err(format!("cannot search lengths {min}..={max}").into());
+    }
+    let payload_kb = match payload_kb.is_empty() {
+        true => crossover_payloads(max, profile)?,
+        false => payload_kb,
+    };
+    // the bench builds only the two collections compared, one at a time,
+    // each holding every payload
+    let budget = HardwareProfile::detect().memory_budget(2);
+
+    let mut searches = Vec::new();
+    for kb in payload_kb {
+        let max = max.min(budget / (kb.max(1) * 1024));
+        if max <= min {
+            eprintln!("napkin: skipping {kb} KB payloads, {min} of them do not fit in memory");
+            continue;
+        }
+        let search = crossover::bisect(min, max, steps, |n| {
+            eprintln!("napkin: measuring {a} and {b} at {kb} KB, N = {n}");
+            measure_pair(a, b, kb, n, lookups, profile)
+        })?;
+        searches.push((kb, search));
+    }
+    print!(
+        "{}",
+        crossover::markdown(a, b, &lookups.lookups(), &searches)
+    );
+    Ok(())
+}
+
+/// Payload sizes `collections-find` sweeps by default when its longest
+/// collection holds `max` payloads. Listing builds no collection, since the
+/// bench builds each one when its first benchmark runs.
+fn crossover_payloads(max: usize, profile: Option<Profile>) -> Result<Vec<usize>, Box<dyn Error>> {
+    let mut command = cargo_bench(&COLLECTIONS_FIND, None, &["--list".to_string()]);
+    command.env(
+        config::SET_VAR,
+        format!("{}.length={max}", COLLECTIONS_FIND.name),
+    );
+    if let Some(profile) = profile {
+        command.env(profile::PROFILE_VAR, profile.to_string());
+    }
+    let output = command.stderr(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "listing `{}` failed: {}",
+            COLLECTIONS_FIND.target, output.status
+        )
+        .into());
+    }
+    let mut sizes = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_suffix(": benchmark")?.rsplit_once('/'))
+        .filter_map(|(_, value)| COLLECTIONS_FIND.parse(value).ok()?.get("payload_kb"))
+        .collect::<Vec<_>>();
+    sizes.sort_unstable();
+    sizes.dedup();
+    Ok(sizes)
+}
+
+/// Runs the benchmarks of `a` and `b` at `payload_kb`, length `n` and
+/// `lookups` only. The filter also keeps the bench from building any other
+/// collection.
+fn measure_pair(
+    a: &str,
+    b: &str,
+    payload_kb: usize,
+    n: usize,
+    lookups: &LookupArgs,
+    profile: Option<Profile>,
+) -> Result<Point, Box<dyn Error>> {
+    let (hit_pct, position, keys) = (
+        lookups.hit_pct,
+        lookups.position.value(),
+        lookups.keys.value(),
+    );
+    let params = COLLECTIONS_FIND.params(&[payload_kb, n, hit_pct, position, keys]);
+    let ids = [a, b].map(|function| COLLECTIONS_FIND.id(function, &params).to_string());
+    let filter = glob::filter_regex(&ids.iter().map(|id| Pattern::new(id)).collect::<Vec<_>>());
+
+    let mut command = cargo_bench(&COLLECTIONS_FIND, None, &[filter]);
+    let name = COLLECTIONS_FIND.name;
+    command.env(
+        config::SET_VAR,
+        format!(
+            "{name}.length={n};{name}.payload_kb={payload_kb};\
+             {name}.hit_pct={hit_pct};{name}.position={position};{name}.keys={keys}"
+        ),
+    );
+    if let Some(profile) = profile {
+        command.env(profile::PROFILE_VAR, profile.to_string());
+    }
+    let started = SystemTime::now();
+    let status = command.stdout(Stdio::null()).status()?;
+    if !status.success() {
+        return Err(format!("running `{}` failed: {status}", COLLECTIONS_FIND.target).into());
+    }
+
+    let mut results = results::collect_since(&paths::criterion_home(), started)?;
+    let mut take = |id: &str| {
+        let at = results.iter().position(|result| result.id == id);
+        at.map(|at| results.swap_remove(at))
+            .ok_or_else(|| format!("`{id}` was not measured, is the function name right?"))
+    };
+    let a = take(&ids[0])?;
+    let b = take(&ids[1])?;
+    Ok(Point { n, a, b })
+}
+
+/// Results selected by `patterns`, from a saved run or from Criterion's latest
+/// measurements, with the profile of the machine that produced them.
+fn load_results(
+    patterns: &[Pattern],
+    run: Option<String>,
+) -> Result<(Vec<BenchResult>, Option<HardwareProfile>), Box<dyn Error>> {
+    let (results, hardware) = match run {
+        Some(spec) => {
+            let run = Run::load(&Run::resolve(&Run::dir(), &spec)?)?;
+            (run.results, Some(run.hardware))
+        }
+        None => {
+            let home = paths::criterion_home();
+            let hardware = HardwareProfile::read(&home.join(hwinfo::PROFILE_FILE)).ok();
+            (results::collect(&home, results::NEW)?, hardware)
+        }
+    };
+    let results = results
+        .into_iter()
+        .filter(|result| is_selected(patterns, &result.id))
+        .collect();
+    Ok((results, hardware))
+}
+
+fn parse_patterns(patterns: &[String]) -> Vec<Pattern> {
+    patterns.iter().map(|p| Pattern::new(p)).collect()
+}
+
+fn is_selected(patterns: &[Pattern], id: &str) -> bool {
+    patterns.is_empty() || patterns.iter().any(|p| p.selects(id))
+}
+
+/// Groups that may hold a benchmark matching any of `patterns`.
+fn selected_groups(patterns: &[Pattern]) -> Vec<&'static Group> {
+    groups::GROUPS
+        .iter()
+        .filter(|group| {
+            patterns.is_empty()
+                || patterns
+                    .iter()
+                    .any(|p| p.selects(group.name) || p.matches_prefix(&format!("{}/", group.name)))
+        })
+        .collect()
+}
+
+fn cargo_bench(group: &Group, features: Option<&str>, args: &[String]) -> Command {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut command = Command::new(cargo);
+    command.args(["bench", "--bench", group.target]);
+    if let Some(features) = features {
+        command.args(["--features", features]);
+    }
+    command.arg("--").args(args);
+    command
+}
@@ -0,0 +1,248 @@
+//! Side-by-side comparison of two runs, usually from different machines.
+//!
+//! Benchmarks with the same id are compared directly. Since size sweeps follow
+//! each machine's caches, many ids only exist on one side, so every
+//! implementation is also compared by its median time per element across all
+//! of its points. Ranking flips list pairs of implementations whose order at a
+//! parameter point differs between the runs, counting only orders whose
+//! confidence intervals do not overlap. Points are matched like the report's
+//! tables, on the dimensions every implementation of a group shares.
+
+use crate::{
+    export::Row,
+    report::{self, format_ns},
+    results::{BenchResult, Run},
+};
+use std::fmt::Write;
+
+/// The same benchmark measured by both runs.
+pub struct Pair<'a> {
+    pub a: &'a BenchResult,
+    pub b: &'a BenchResult,
+}
+
+impl Pair<'_> {
+    /// Mean time on `b` relative to `a`.
+    pub fn ratio(&self) -> f64 {
+        self.b.mean.point_estimate / self.a.mean.point_estimate
+    }
+}
+
+/// Median time per element of one implementation on each run.
+pub struct PerElement {
+    pub group: String,
+    pub function: String,
+    pub a_ns: f64,
+    pub b_ns: f64,
+}
+
+/// Two implementations ordered one way on `a` and the other way on `b`.
+pub struct Flip {
+    pub group: String,
+    pub parameter: String,
+    /// Faster on `a`, slower on `b`.
+    pub first: String,
+    /// Slower on `a`, faster on `b`.
+    pub second: String,
+    /// How much faster `first` is on `a`.
+    pub a_ratio: f64,
+    /// How much faster `second` is on `b`.
+    pub b_ratio: f64,
+}
+
+pub struct Comparison<'a> {
+    pub pairs: Vec<Pair<'a>>,
+    pub per_element: Vec<PerElement>,
+    pub flips: Vec<Flip>,
+    pub only_a: Vec<&'a BenchResult>,
+    pub only_b: Vec<&'a BenchResult>,
+}
+
+pub fn compare<'a>(a: &'a [BenchResult], b: &'a [BenchResult]) -> Comparison<'a> {
+    let mut pairs = Vec::new();
+    let mut only_a = Vec::new();
+    for ra in a {
+        match b.iter().find(|rb| rb.id == ra.id) {
+            Some(rb) => pairs.push(Pair { a: ra, b: rb }),
+            None => only_a.push(ra),
+        }
+    }
+    let only_b = b
+        .iter()
+        .filter(|rb| !a.iter().any(|ra| ra.id == rb.id))
+        .collect();
+
+    Comparison {
+        per_element: per_element(a, b),
+        flips: flips(&pairs),
+        pairs,
+        only_a,
+        only_b,
+    }
+}
+
+fn per_element(a: &[BenchResult], b: &[BenchResult]) -> Vec<PerElement> {
+    let mut implementations: Vec<(&str, &str)> = Vec::new();
+    for result in a {
+        let key = (
+            result.group.as_str(),
+            result.function.as_deref().unwrap_or(""),
+        );
+        if !implementations.contains(&key) {
+            implementations.push(key);
+        }
+    }
+
+    implementations
+        .into_iter()
+        .filter_map(|(group, function)| {
+            Some(PerElement {
+                group: group.to_string(),
+                function: function.to_string(),
+                a_ns: median_per_element(a, group, function)?,
+                b_ns: median_per_element(b, group, function)?,
+            })
+        })
+        .collect()
+}
+
+fn median_per_element(results: &[BenchResult], group: &str, function: &str) -> Option<f64> {
+    let mut times = results
+        .iter()
+        .filter(|r| r.group == group && r.function.as_deref().unwrap_or("") == function)
+        .filter_map(BenchResult::ns_per_op)
+        .collect::<Vec<_>>();
+    if times.is_empty() {
+        return None;
+    }
+    times.sort_by(f64::total_cmp);
+    Some(times[times.len() / 2])
+}
+
+fn flips(pairs: &[Pair]) -> Vec<Flip> {
+    // `x` is faster than `y` when its whole confidence interval is below `y`'s
+    let faster = |x: &BenchResult, y: &BenchResult| {
+        x.mean.confidence_interval.upper_bound < y.mean.confidence_interval.lower_bound
+    };
+
+    // every pair with the point it is ranked at and the name it is ranked under
+    let rows = pairs
+        .iter()
+        .map(|pair| Row::from(pair.a))
+        .collect::<Vec<_>>();
+    let shared = report::shared_dims(&rows);
+    let ranked = pairs
+        .iter()
+        .zip(&rows)
+        .map(|(pair, row)| {
+            let dims = shared
+                .iter()
+                .find(|(group, _)| *group == row.group)
+                .map_or(0, |(_, dims)| *dims);
+            let point = report::shared_parameter(row, dims);
+            (pair, point, report::label(row, dims))
+        })
+        .collect::<Vec<_>>();
+
+    let mut flips = Vec::new();
+    for (i, (x, point, x_name)) in ranked.iter().enumerate() {
+        for (y, y_point, y_name) in &ranked[i + 1..] {
+            if x.a.group != y.a.group || point != y_point {
+                continue;
+            }
+            let ((first, first_name), (second, second_name)) =
+                if faster(x.a, y.a) && faster(y.b, x.b) {
+                    ((x, x_name), (y, y_name))
+                } else if faster(y.a, x.a) && faster(x.b, y.b) {
+                    ((y, y_name), (x, x_name))
+                } else {
+                    continue;
+                };
+            flips.push(Flip {
+                group: x.a.group.clone(),
+                parameter: point.clone(),
+                first: first_name.clone(),
+                second: second_name.clone(),
+                a_ratio: second.a.mean.point_estimate / first.a.mean.point_estimate,
+                b_ratio: first.b.mean.point_estimate / second.b.mean.point_estimate,
+            });
+        }
+    }
+    flips
+}
+
+/// Renders the comparison of run `a` against run `b` as Markdown.
+pub fn markdown(a: &Run, b: &Run, comparison: &Comparison) -> String {
+    let mut out = String::new();
+    let label = |run: &Run| {
+        format!(
+            "`{}/{}` ({})",
+            run.hardware.fingerprint(),
+            run.id,
+            run.hardware.model_name.as_deref().unwrap_or("unknown CPU")
+        )
+    };
+    let _ = writeln!(out, "A: {}\n\nB: {}\n", label(a), label(b));
+
+    if !comparison.flips.is_empty() {
+        let _ = writeln!(out, "### Ranking flips\n");
+        for flip in &comparison.flips {
+            let _ = writeln!(
+                out,
+                "- **{} @ {}**: {} beats {} on A ({:.2}x) but {} beats {} on B ({:.2}x)",
+                flip.group,
+                flip.parameter,
+                flip.first,
+                flip.second,
+                flip.a_ratio,
+                flip.second,
+                flip.first,
+                flip.b_ratio
+            );
+        }
+        out.push('\n');
+    }
+
+    if !comparison.per_element.is_empty() {
+        let _ = writeln!(out, "### Median time per element\n");
+        let _ = writeln!(out, "| Group | Implementation | A | B | B/A |");
+        let _ = writeln!(out, "|---|---|---:|---:|---:|");
+        for row in &comparison.per_element {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {:.2}x |",
+                row.group,
+                row.function,
+                format_ns(row.a_ns),
+                format_ns(row.b_ns),
+                row.b_ns / row.a_ns
+            );
+        }
+        out.push('\n');
+    }
+
+    if !comparison.pairs.is_empty() {
+        let _ = writeln!(out, "### Benchmarks on both runs\n");
+        let _ = writeln!(out, "| Benchmark | A | B | B/A |");
+        let _ = writeln!(out, "|---|---:|---:|---:|");
+        for pair in &comparison.pairs {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {:.2}x |",
+                pair.a.id,
+                format_ns(pair.a.mean.point_estimate),
+                format_ns(pair.b.mean.point_estimate),
+                pair.ratio()
+            );
+        }
+        out.push('\n');
+    }
+
+    let _ = writeln!(
+        out,
+        "{} benchmarks only on A, {} only on B.",
+        comparison.only_a.len(),
+        comparison.only_b.len()
+    );
+    out
+}
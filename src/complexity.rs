@@ -0,0 +1,334 @@
+//! Empirical complexity of each implementation along a size dimension.
+//!
+//! Every series of benchmarks of one implementation that differ only in one
+//! size dimension is fitted to `t(n) = a + b·g(n)` for each [`Model`], by
+//! least squares on relative residuals so the smallest sizes weigh as much as
+//! the largest. Among growing models with a plausible intercept, the slowest
+//! growing one whose residuals are close to the smallest wins, unless it
+//! predicts little growth, in which case the series is reported as constant.
+//! Groups declare the model each implementation should follow. A series that
+//! should grow but does not usually means the work was optimized away or the
+//! timing is dominated by something else. Logarithms grow so slowly that a
+//! series expected to follow one needs more sizes, and less growth, before it
+//! is judged.
+
+use crate::{export::Row, glob::Pattern, groups};
+use std::{cmp::Ordering, fmt};
+
+/// Series with fewer distinct sizes are not fitted.
+pub const MIN_POINTS: usize = 3;
+/// Share of the variation a growing model must explain before it is judged
+/// against the expected one.
+pub const MIN_R_SQUARED: f64 = 0.9;
+/// Growth a growing model must predict across the series, relative to the
+/// time at its smallest size.
+const MIN_GROWTH: f64 = 0.25;
+/// Sizes a series expected to be `O(log n)` needs before it is judged: over
+/// 10 to 1000, `log n` only triples, and the fit cannot tell it from a
+/// constant.
+pub const MIN_LOG_POINTS: usize = 5;
+/// Growth an `O(log n)` fit must predict when that is the model expected, as
+/// lookups walking a few nodes are often dominated by their constant part.
+const MIN_LOG_GROWTH: f64 = 0.05;
+/// Lowest intercept a growing model may have, relative to the time at the
+/// smallest size. A much negative one means the model grows too fast there.
+const MIN_INTERCEPT: f64 = -0.25;
+/// A model is preferred to any faster-growing one whose residuals are not at
+/// least this many times smaller, so noise alone does not pick `O(n log n)`
+/// over `O(n)`.
+const SIMPLER_BY: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Model {
+    Constant,
+    Log,
+    Linear,
+    NLogN,
+    /// E.g. `tokio-find` along `indices`, where each of n requests scans n
+    /// entries.
+    Quadratic,
+}
+
+impl Model {
+    const GROWING: [Model; 4] = [Model::Log, Model::Linear, Model::NLogN, Model::Quadratic];
+
+    /// `g(n)`, the term the slope multiplies.
+    fn term(self, n: f64) -> f64 {
+        match self {
+            Model::Constant => 0.0,
+            Model::Log => n.log2(),
+            Model::Linear => n,
+            Model::NLogN => n * n.log2(),
+            Model::Quadratic => n * n,
+        }
+    }
+
+    /// `g(n)` as written in reports.
+    pub fn term_name(self) -> &'static str {
+        match self {
+            Model::Constant => "",
+            Model::Log => "log₂n",
+            Model::Linear => "n",
+            Model::NLogN => "n·log₂n",
+            Model::Quadratic => "n²",
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Model::Constant => "O(1)",
+            Model::Log => "O(log n)",
+            Model::Linear => "O(n)",
+            Model::NLogN => "O(n log n)",
+            Model::Quadratic => "O(n²)",
+        })
+    }
+}
+
+/// The model the implementations matching `functions` (a glob over function
+/// names) should follow along `dim`, in series where the other dimensions
+/// take the values in `when`. The first matching expectation applies.
+#[derive(Debug, Clone, Copy)]
+pub struct Expectation {
+    pub dim: &'static str,
+    pub functions: &'static str,
+    pub model: Model,
+    /// `(dim, value)` pairs the series must be fixed at, e.g. lookups that
+    /// all hit the front of a sequence.
+    pub when: &'static [(&'static str, usize)],
+}
+
+/// How a fit departs from the expected model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Does not grow although the implementation must: likely optimized away
+    /// or dominated by setup.
+    NoGrowth,
+    /// Grows, but slower than expected.
+    Flatter,
+    /// Grows faster than expected, e.g. from cache misses or contention.
+    Steeper,
+    /// No model explains the series well enough to judge it.
+    PoorFit,
+    /// Too few sizes to tell the expected `O(log n)` from a constant, see
+    /// [`MIN_LOG_POINTS`].
+    TooFewSizes,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Verdict::NoGrowth => "no growth: likely optimized away or mis-measured",
+            Verdict::Flatter => "flatter than expected",
+            Verdict::Steeper => "steeper than expected: cache or contention effects?",
+            Verdict::PoorFit => "poor fit: too noisy to judge",
+            Verdict::TooFewSizes => "too few sizes to judge O(log n)",
+        })
+    }
+}
+
+/// `t(n) = intercept + slope·g(n)`, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Curve {
+    pub model: Model,
+    pub intercept: f64,
+    /// Zero for constants.
+    pub slope: f64,
+    /// Share of the variation explained, for growing models.
+    pub r_squared: Option<f64>,
+}
+
+/// The best model of one series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    pub group: String,
+    pub implementation: String,
+    /// The dimension the series varies along.
+    pub dim: &'static str,
+    /// The other dimensions, fixed along the series.
+    pub fixed: Vec<(&'static str, usize)>,
+    /// Sizes measured.
+    pub points: usize,
+    pub curve: Curve,
+    pub expected: Option<Model>,
+}
+
+impl Fit {
+    /// How the fit departs from the expected model, `None` when it matches
+    /// or nothing is expected.
+    pub fn verdict(&self) -> Option<Verdict> {
+        let expected = self.expected?;
+        let model = self.curve.model;
+        if expected == Model::Log && self.points < MIN_LOG_POINTS {
+            return Some(Verdict::TooFewSizes);
+        }
+        if self.curve.r_squared.is_some_and(|r| r < MIN_R_SQUARED) {
+            return Some(Verdict::PoorFit);
+        }
+        match model.cmp(&expected) {
+            Ordering::Equal => None,
+            Ordering::Less if model == Model::Constant => Some(Verdict::NoGrowth),
+            Ordering::Less => Some(Verdict::Flatter),
+            Ordering::Greater => Some(Verdict::Steeper),
+        }
+    }
+}
+
+/// Fits every series along the dimensions each group declares expectations
+/// for, in registry order.
+pub fn fits(rows: &[Row]) -> Vec<Fit> {
+    let mut fits = Vec::new();
+    for group in groups::GROUPS {
+        let mut dims = Vec::new();
+        for expectation in group.complexity {
+            if !dims.contains(&expectation.dim) {
+                dims.push(expectation.dim);
+            }
+        }
+        for dim in dims {
+            for series in series(rows, group.name, dim) {
+                let expected = group
+                    .complexity
+                    .iter()
+                    .find(|e| {
+                        e.dim == dim
+                            && Pattern::new(e.functions).matches(&series.function)
+                            && e.when.iter().all(|fixed| series.fixed.contains(fixed))
+                    })
+                    .map(|e| e.model);
+                let Some(curve) = fit(&series.points, expected) else {
+                    continue;
+                };
+                fits.push(Fit {
+                    group: group.name.to_string(),
+                    implementation: series.function,
+                    dim,
+                    fixed: series.fixed,
+                    points: series.points.len(),
+                    curve,
+                    expected,
+                });
+            }
+        }
+    }
+    fits
+}
+
+struct Series {
+    function: String,
+    fixed: Vec<(&'static str, usize)>,
+    /// `(n, mean ns)`, sorted by `n`.
+    points: Vec<(f64, f64)>,
+}
+
+/// Splits the rows of `group` into series along `dim`.
+fn series(rows: &[Row], group: &str, dim: &'static str) -> Vec<Series> {
+    let mut series: Vec<Series> = Vec::new();
+    for row in rows.iter().filter(|row| row.group == group) {
+        let Some(n) = row.param(dim) else {
+            continue;
+        };
+        let fixed = row
+            .params
+            .iter()
+            .copied()
+            .filter(|(name, _)| *name != dim)
+            .collect::<Vec<_>>();
+        let point = (n as f64, row.mean.point);
+        match series
+            .iter_mut()
+            .find(|s| s.function == row.implementation && s.fixed == fixed)
+        {
+            Some(s) => s.points.push(point),
+            None => series.push(Series {
+                function: row.implementation.clone(),
+                fixed,
+                points: vec![point],
+            }),
+        }
+    }
+    for s in &mut series {
+        s.points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+    series.retain(|s| {
+        let mut sizes = s.points.iter().map(|p| p.0).collect::<Vec<_>>();
+        sizes.dedup();
+        sizes.len() >= MIN_POINTS
+    });
+    series
+}
+
+/// Weighted least squares of `t = a + b·g(n)`, returning `a`, `b` and the
+/// weighted residual sum of squares.
+fn least_squares(points: &[(f64, f64)], model: Model) -> Option<(f64, f64, f64)> {
+    // weights of 1/t² turn residuals into relative ones
+    let weighted = points
+        .iter()
+        .map(|&(n, t)| (model.term(n), t, 1.0 / (t * t)));
+    let sw: f64 = weighted.clone().map(|(_, _, w)| w).sum();
+    let xm = weighted.clone().map(|(x, _, w)| w * x).sum::<f64>() / sw;
+    let tm = weighted.clone().map(|(_, t, w)| w * t).sum::<f64>() / sw;
+    let sxx: f64 = weighted.clone().map(|(x, _, w)| w * (x - xm).powi(2)).sum();
+    let sxt: f64 = weighted
+        .clone()
+        .map(|(x, t, w)| w * (x - xm) * (t - tm))
+        .sum();
+
+    let (a, b) = match model {
+        Model::Constant => (tm, 0.0),
+        _ if sxx > 0.0 => (tm - sxt / sxx * xm, sxt / sxx),
+        _ => return None,
+    };
+    let rss = weighted.map(|(x, t, w)| w * (t - a - b * x).powi(2)).sum();
+    Some((a, b, rss))
+}
+
+/// The best model of a series sorted by size, expected to follow `expected`.
+fn fit(points: &[(f64, f64)], expected: Option<Model>) -> Option<Curve> {
+    if points.iter().any(|&(_, t)| t.is_nan() || t <= 0.0) {
+        return None;
+    }
+    let (mean, _, total) = least_squares(points, Model::Constant)?;
+    let constant = Curve {
+        model: Model::Constant,
+        intercept: mean,
+        slope: 0.0,
+        r_squared: None,
+    };
+    if total == 0.0 {
+        return Some(constant);
+    }
+
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let candidates = Model::GROWING
+        .into_iter()
+        .filter_map(|model| {
+            let (a, b, rss) = least_squares(points, model)?;
+            (b > 0.0 && a >= MIN_INTERCEPT * first.1).then_some((model, a, b, rss))
+        })
+        .collect::<Vec<_>>();
+    let Some(min_rss) = candidates.iter().map(|c| c.3).min_by(f64::total_cmp) else {
+        return Some(constant);
+    };
+    // candidates are ordered from the slowest growing model
+    let (model, intercept, slope, rss) = *candidates
+        .iter()
+        .find(|c| c.3 <= SIMPLER_BY * min_rss)
+        .unwrap();
+    let growth = slope * (model.term(last.0) - model.term(first.0)) / first.1;
+    let min_growth = match (model, expected) {
+        (Model::Log, Some(Model::Log)) => MIN_LOG_GROWTH,
+        _ => MIN_GROWTH,
+    };
+    if growth < min_growth {
+        return Some(constant);
+    }
+    Some(Curve {
+        model,
+        intercept,
+        slope,
+        r_squared: Some(1.0 - rss / total),
+    })
+}
@@ -0,0 +1,150 @@
+//! Parameter matrices read from `napkin.toml`.
+//!
+//! Each group sweeps the values its bench target hardcodes unless a table
+//! named after the group lists others for a dimension:
+//!
+//! ```toml
+//! [tokio-increment]
+//! tasks = [4, 8]
+//! increments = [10_000]
+//! ```
+//!
+//! The file is `napkin.toml` in the working directory, or the path in
+//! [`CONFIG_VAR`]. Overrides in [`SET_VAR`], such as
+//! `tokio-increment.tasks=4,8;memory-access.size_bytes=65536`, take precedence
+//! over the file.
+
+use crate::groups;
+use std::{collections::BTreeMap, env, error::Error, fmt, fs, path::PathBuf, sync::OnceLock};
+
+/// Path of the TOML file, `napkin.toml` when unset.
+pub const CONFIG_VAR: &str = "NAPKIN_CONFIG";
+/// `group.dim=v1,v2` overrides, separated by `;`.
+pub const SET_VAR: &str = "NAPKIN_SET";
+
+/// Values to sweep, by group then dimension.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Matrix {
+    values: BTreeMap<String, BTreeMap<String, Vec<usize>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    source: String,
+    reason: String,
+}
+
+impl ConfigError {
+    fn new(source: &str, reason: impl Into<String>) -> Self {
+        Self {
+            source: source.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid parameter matrix in {}: {}",
+            self.source, self.reason
+        )
+    }
+}
+
+impl Error for ConfigError {}
+
+impl Matrix {
+    /// Parses a TOML matrix; `source` names it in errors.
+    pub fn from_toml(source: &str, text: &str) -> Result<Self, ConfigError> {
+        let values = toml::from_str(text).map_err(|e| ConfigError::new(source, e.to_string()))?;
+        let matrix = Self { values };
+        matrix.validate(source)?;
+        Ok(matrix)
+    }
+
+    /// Parses `group.dim=v1,v2;...` overrides; `source` names them in errors.
+    pub fn from_overrides(source: &str, text: &str) -> Result<Self, ConfigError> {
+        let mut matrix = Self::default();
+        for entry in text.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let invalid =
+                || ConfigError::new(source, format!("expected `group.dim=v1,v2`, got `{entry}`"));
+            let (key, list) = entry.split_once('=').ok_or_else(invalid)?;
+            let (group, dim) = key.trim().rsplit_once('.').ok_or_else(invalid)?;
+            let values = list
+                .split(',')
+                .map(|v| v.trim().replace('_', "").parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+            matrix
+                .values
+                .entry(group.to_string())
+                .or_default()
+                .insert(dim.to_string(), values);
+        }
+        matrix.validate(source)?;
+        Ok(matrix)
+    }
+
+    /// Reads the TOML file, if any, then applies the overrides in
+    /// [`SET_VAR`].
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = env::var_os(CONFIG_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("napkin.toml"));
+        let source = path.display().to_string();
+        let mut matrix = match fs::read_to_string(&path) {
+            Ok(text) => Self::from_toml(&source, &text)?,
+            Err(_) if env::var_os(CONFIG_VAR).is_none() => Self::default(),
+            Err(e) => return Err(ConfigError::new(&source, e.to_string())),
+        };
+        if let Ok(text) = env::var(SET_VAR) {
+            matrix.merge(Self::from_overrides(SET_VAR, &text)?);
+        }
+        Ok(matrix)
+    }
+
+    /// Replaces the values of every dimension `other` configures.
+    pub fn merge(&mut self, other: Matrix) {
+        for (group, dims) in other.values {
+            self.values.entry(group).or_default().extend(dims);
+        }
+    }
+
+    /// Configured values of `dim` in `group`.
+    pub fn get(&self, group: &str, dim: &str) -> Option<&[usize]> {
+        self.values.get(group)?.get(dim).map(Vec::as_slice)
+    }
+
+    fn validate(&self, source: &str) -> Result<(), ConfigError> {
+        for (name, dims) in &self.values {
+            let group = groups::find(name)
+                .ok_or_else(|| ConfigError::new(source, format!("unknown group `{name}`")))?;
+            for (dim, values) in dims {
+                if !group.has_dim(dim) {
+                    let known = group.dims.iter().map(|d| d.name).collect::<Vec<_>>();
+                    return Err(ConfigError::new(
+                        source,
+                        format!("`{name}` has no dimension `{dim}`, expected one of {known:?}"),
+                    ));
+                }
+                if values.is_empty() {
+                    return Err(ConfigError::new(source, format!("`{name}.{dim}` is empty")));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The matrix of this process, loaded on first use.
+///
+/// # Panics
+///
+/// Panics if the file or the overrides are invalid, so a typo fails the run
+/// instead of silently sweeping the defaults.
+pub fn matrix() -> &'static Matrix {
+    static MATRIX: OnceLock<Matrix> = OnceLock::new();
+    MATRIX.get_or_init(|| Matrix::load().unwrap_or_else(|e| panic!("{e}")))
+}
@@ -0,0 +1,171 @@
+//! Crossover search between two implementations of `collections-find`.
+//!
+//! A linear scan beats hashing on short collections and loses on long ones.
+//! Instead of reading the crossover off a coarse sweep, the search measures
+//! both implementations at the ends of a range of lengths, then bisects it
+//! geometrically, keeping the half where the faster implementation changes.
+//! Its confidence is the probability, from the means and their standard
+//! errors, that the order observed on each side of the crossover is real.
+
+use crate::{
+    report::format_ns,
+    results::BenchResult,
+    workload::{KeyDistribution, KeyPosition},
+};
+use std::fmt::{self, Write};
+
+/// Both implementations measured at one length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    pub n: usize,
+    pub a: BenchResult,
+    pub b: BenchResult,
+}
+
+impl Point {
+    pub fn a_faster(&self) -> bool {
+        self.a.mean.point_estimate < self.b.mean.point_estimate
+    }
+
+    /// Probability that the faster implementation really is faster here,
+    /// assuming normally distributed means.
+    pub fn confidence(&self) -> f64 {
+        let (a, b) = (&self.a.mean, &self.b.mean);
+        let se = a.standard_error.hypot(b.standard_error);
+        if se == 0.0 {
+            return 1.0;
+        }
+        normal_cdf((a.point_estimate - b.point_estimate).abs() / se)
+    }
+}
+
+/// Outcome of a search over one range of lengths.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Crossover {
+    /// The faster implementation changes between `below.n` and `above.n`,
+    /// the smallest bracket the search reached.
+    Between { below: Point, above: Point },
+    /// The same implementation is faster at both ends of the range.
+    Outside { min: Point, max: Point },
+}
+
+impl Crossover {
+    /// Probability that the reported outcome holds: that the orders at both
+    /// ends of the bracket, or of the range, are real.
+    pub fn confidence(&self) -> f64 {
+        let (lo, hi) = match self {
+            Crossover::Between { below, above } => (below, above),
+            Crossover::Outside { min, max } => (min, max),
+        };
+        lo.confidence() * hi.confidence()
+    }
+}
+
+/// Bisects `min..=max` for the length where the faster implementation
+/// changes, measuring at most `steps` lengths between the two ends.
+pub fn bisect<E>(
+    min: usize,
+    max: usize,
+    steps: usize,
+    mut measure: impl FnMut(usize) -> Result<Point, E>,
+) -> Result<Crossover, E> {
+    let mut lo = measure(min)?;
+    let mut hi = measure(max)?;
+    if lo.a_faster() == hi.a_faster() {
+        return Ok(Crossover::Outside { min: lo, max: hi });
+    }
+    for _ in 0..steps {
+        // lengths span orders of magnitude, so split the range in log space
+        let mid = ((lo.n as f64) * (hi.n as f64)).sqrt().round() as usize;
+        if mid <= lo.n || mid >= hi.n {
+            break;
+        }
+        let point = measure(mid)?;
+        if point.a_faster() == lo.a_faster() {
+            lo = point;
+        } else {
+            hi = point;
+        }
+    }
+    Ok(Crossover::Between {
+        below: lo,
+        above: hi,
+    })
+}
+
+/// The keys and lookups of the searched benchmarks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lookups {
+    pub keys: KeyDistribution,
+    pub hit_pct: usize,
+    pub position: KeyPosition,
+}
+
+impl fmt::Display for Lookups {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} keys, ", self.keys)?;
+        match self.position {
+            KeyPosition::Absent => f.write_str("every lookup misses"),
+            position => write!(f, "{}% hit the key at the {position}", self.hit_pct),
+        }
+    }
+}
+
+/// Renders one row per payload size.
+pub fn markdown(a: &str, b: &str, lookups: &Lookups, searches: &[(usize, Crossover)]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "## Crossover: {a} vs {b}\n");
+    let _ = writeln!(out, "Lookups: {lookups}.\n");
+    let _ = writeln!(
+        out,
+        "| Payload | Crossover | Faster below | Faster above | {a} | {b} | Confidence |"
+    );
+    let _ = writeln!(out, "|---:|---|---|---|---|---|---:|");
+    let faster = |point: &Point| if point.a_faster() { a } else { b };
+    // times at both ends of the bracket or range
+    let times = |lo: &Point, hi: &Point, pick: fn(&Point) -> &BenchResult| {
+        format!(
+            "{} → {}",
+            format_ns(pick(lo).mean.point_estimate),
+            format_ns(pick(hi).mean.point_estimate)
+        )
+    };
+    for (payload_kb, crossover) in searches {
+        let (lo, hi, at) = match crossover {
+            Crossover::Between { below, above } if below.n + 1 == above.n => {
+                (below, above, format!("N = {}", above.n))
+            }
+            Crossover::Between { below, above } => {
+                (below, above, format!("N in {}..={}", below.n + 1, above.n))
+            }
+            Crossover::Outside { min, max } => (min, max, format!("none in {}..={}", min.n, max.n)),
+        };
+        let _ = writeln!(
+            out,
+            "| {payload_kb} KB | {at} | {} | {} | {} | {} | {:.1}% |",
+            faster(lo),
+            faster(hi),
+            times(lo, hi, |p| &p.a),
+            times(lo, hi, |p| &p.b),
+            crossover.confidence() * 100.0,
+        );
+    }
+    out
+}
+
+/// Standard normal CDF, from the Abramowitz and Stegun approximation of erf
+/// (absolute error below 1.5e-7).
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
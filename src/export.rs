@@ -0,0 +1,258 @@
+//! Tidy CSV and JSON tables of benchmark results.
+//!
+//! Every row is one benchmark: its group, implementation (Criterion's function
+//! id), raw parameter, one column per parameter dimension of any group (empty
+//! when the row's group has no such dimension, and the variant name for
+//! dimensions enumerating variants such as `keys`), the mean, median and
+//! standard deviation with their confidence intervals, in nanoseconds, the
+//! per-operation cost and bandwidth derived from the work the group declares,
+//! the allocations and performance counters per iteration when they were
+//! recorded, and the per-operation latency percentiles of groups recording
+//! them.
+
+use crate::{
+    alloc::AllocStats,
+    groups::{self, Work},
+    latency::LatencyStats,
+    perf::{self, PerfStats},
+    results::{BenchResult, Estimate},
+};
+use serde_json::{Map, Value, json};
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub group: String,
+    pub implementation: String,
+    pub parameter: String,
+    pub params: Vec<(&'static str, usize)>,
+    pub work: Option<Work>,
+    pub mean: Stat,
+    pub median: Stat,
+    pub std_dev: Stat,
+    pub alloc: Option<AllocStats>,
+    pub perf: Option<PerfStats>,
+    pub latency: Option<LatencyStats>,
+}
+
+/// A point estimate and its confidence interval, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stat {
+    pub point: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl From<&Estimate> for Stat {
+    fn from(estimate: &Estimate) -> Self {
+        Self {
+            point: estimate.point_estimate,
+            lower: estimate.confidence_interval.lower_bound,
+            upper: estimate.confidence_interval.upper_bound,
+        }
+    }
+}
+
+impl From<&BenchResult> for Row {
+    fn from(result: &BenchResult) -> Self {
+        Self {
+            group: result.group.clone(),
+            implementation: result.function.clone().unwrap_or_default(),
+            parameter: result.value.clone().unwrap_or_default(),
+            params: result
+                .params()
+                .map(|params| params.iter().collect())
+                .unwrap_or_default(),
+            work: result.work(),
+            mean: Stat::from(&result.mean),
+            median: Stat::from(&result.median),
+            std_dev: Stat::from(&result.std_dev),
+            alloc: result.alloc,
+            perf: result.perf.clone(),
+            latency: result.latency,
+        }
+    }
+}
+
+impl Row {
+    pub fn param(&self, name: &str) -> Option<usize> {
+        self.params
+            .iter()
+            .find(|(dim, _)| *dim == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Mean nanoseconds per element (operation).
+    pub fn ns_per_op(&self) -> Option<f64> {
+        Some(self.mean.point / self.work?.elements as f64)
+    }
+
+    /// Mean bandwidth in GB/s, when the group declares the bytes touched.
+    pub fn gb_per_s(&self) -> Option<f64> {
+        Some(self.work?.bytes? as f64 / self.mean.point)
+    }
+
+    /// Performance counter `name` per iteration, when it was active.
+    pub fn counter(&self, name: &str) -> Option<f64> {
+        self.perf.as_ref()?.counters.get(name).copied()
+    }
+}
+
+pub fn rows(results: &[BenchResult]) -> Vec<Row> {
+    results.iter().map(Row::from).collect()
+}
+
+/// Every dimension declared by a known group, in declaration order.
+pub fn dimensions() -> Vec<&'static str> {
+    let mut dims = Vec::new();
+    for dim in groups::GROUPS.iter().flat_map(|group| group.dims) {
+        if !dims.contains(&dim.name) {
+            dims.push(dim.name);
+        }
+    }
+    dims
+}
+
+/// One statistic of a row.
+type StatOf = fn(&Row) -> Stat;
+
+/// The statistics of every row, each exported with its confidence interval.
+const STATS: [(&str, StatOf); 3] = [
+    ("mean", |row| row.mean),
+    ("median", |row| row.median),
+    ("std_dev", |row| row.std_dev),
+];
+
+/// One column of the table: its name, and the row's value in it, `null` (an
+/// empty CSV field) when the row has none.
+type Column = (String, Box<dyn Fn(&Row) -> Value>);
+
+fn column(name: impl Into<String>, value: impl Fn(&Row) -> Value + 'static) -> Column {
+    (name.into(), Box::new(value))
+}
+
+/// Every column of the table, in order, as both [`write_csv`] and
+/// [`to_json`] write it.
+fn columns() -> Vec<Column> {
+    let mut columns = vec![
+        column("group", |row| json!(row.group)),
+        column("implementation", |row| json!(row.implementation)),
+        column("parameter", |row| json!(row.parameter)),
+    ];
+    for dim in dimensions() {
+        columns.push(column(dim, move |row| {
+            let value = row.param(dim).map(|v| match groups::value_name(dim, v) {
+                Some(name) => json!(name),
+                None => json!(v),
+            });
+            json!(value)
+        }));
+    }
+    for (stat, get) in STATS {
+        columns.extend([
+            column(format!("{stat}_ns"), move |row| json!(get(row).point)),
+            column(format!("{stat}_lower_ns"), move |row| json!(get(row).lower)),
+            column(format!("{stat}_upper_ns"), move |row| json!(get(row).upper)),
+        ]);
+    }
+    columns.extend([
+        column("elements", |row| json!(row.work.map(|w| w.elements))),
+        column("bytes", |row| json!(row.work.and_then(|w| w.bytes))),
+        column("ns_per_op", |row| json!(row.ns_per_op())),
+        column("gb_per_s", |row| json!(row.gb_per_s())),
+        column("allocs_per_iter", |row| json!(row.alloc.map(|a| a.allocs))),
+        column("frees_per_iter", |row| json!(row.alloc.map(|a| a.frees))),
+        column("alloc_bytes_per_iter", |row| {
+            json!(row.alloc.map(|a| a.bytes))
+        }),
+        column("counter_source", |row| {
+            json!(row.perf.as_ref().map(|p| source_name(p.source)))
+        }),
+    ]);
+    for event in perf::HARDWARE.iter().chain(perf::SOFTWARE) {
+        columns.push(column(format!("{}_per_iter", event.name), |row| {
+            json!(row.counter(event.name))
+        }));
+    }
+    columns.extend([
+        column("latency_ops", |row| {
+            json!(row.latency.map(|l| l.operations))
+        }),
+        column("p50_ns", |row| json!(row.latency.map(|l| l.p50_ns))),
+        column("p99_ns", |row| json!(row.latency.map(|l| l.p99_ns))),
+        column("p999_ns", |row| json!(row.latency.map(|l| l.p999_ns))),
+        column("max_ns", |row| json!(row.latency.map(|l| l.max_ns))),
+    ]);
+    columns
+}
+
+pub fn write_csv(rows: &[Row], mut out: impl Write) -> io::Result<()> {
+    let columns = columns();
+    let header = columns.iter().map(|(name, _)| name.as_str());
+    writeln!(out, "{}", header.collect::<Vec<_>>().join(","))?;
+    for row in rows {
+        let fields = columns.iter().map(|(_, value)| csv_value(&value(row)));
+        writeln!(out, "{}", fields.collect::<Vec<_>>().join(","))?;
+    }
+    Ok(())
+}
+
+/// The same table as [`write_csv`], as an array of flat JSON objects.
+pub fn to_json(rows: &[Row]) -> Value {
+    let columns = columns();
+    let rows = rows
+        .iter()
+        .map(|row| {
+            let object = columns
+                .iter()
+                .map(|(name, value)| (name.clone(), value(row)))
+                .collect::<Map<_, _>>();
+            Value::Object(object)
+        })
+        .collect();
+    Value::Array(rows)
+}
+
+/// Writes `results.csv` and `results.json` into `dir`.
+pub fn write_all(rows: &[Row], dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut csv = io::BufWriter::new(fs::File::create(dir.join("results.csv"))?);
+    write_csv(rows, &mut csv)?;
+    csv.flush()?;
+    let json = serde_json::to_string_pretty(&to_json(rows)).map_err(io::Error::other)?;
+    fs::write(dir.join("results.json"), json)
+}
+
+fn source_name(source: perf::Source) -> &'static str {
+    match source {
+        perf::Source::Hardware => "hardware",
+        perf::Source::Software => "software",
+    }
+}
+
+/// A cell as a CSV field: empty for `null`, and numbers written as Rust
+/// writes them.
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => csv_field(text),
+        Value::Number(n) => match n.as_u64() {
+            Some(n) => n.to_string(),
+            None => n.as_f64().unwrap_or_default().to_string(),
+        },
+        other => csv_field(&other.to_string()),
+    }
+}
+
+/// Quotes a CSV field when it holds a separator, quote or newline.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
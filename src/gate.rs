@@ -0,0 +1,138 @@
+//! Noise-aware regression gate against a saved Criterion baseline.
+//!
+//! A benchmark regresses when its mean grew by more than
+//! `threshold + noise_factor * noise`, where `noise` is the larger relative
+//! standard deviation of the two measurements, and its confidence interval lies
+//! entirely above the baseline's. Noisy benchmarks therefore need a larger
+//! change to fail the gate than stable ones. A baseline benchmark without a
+//! current measurement is missing, so a gate that measured nothing fails.
+
+use crate::{report::format_ns, results::BenchResult};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gate {
+    /// Relative change always tolerated, e.g. `0.02` for 2%.
+    pub threshold: f64,
+    /// How many relative standard deviations of noise are tolerated on top.
+    pub noise_factor: f64,
+}
+
+impl Default for Gate {
+    fn default() -> Self {
+        Self {
+            threshold: 0.02,
+            noise_factor: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Regressed,
+    Improved,
+    Unchanged,
+    /// In the baseline but not measured since.
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub id: String,
+    pub baseline_ns: f64,
+    /// `None` when the benchmark is missing, as are the change and limit.
+    pub current_ns: Option<f64>,
+    /// Relative change of the mean, e.g. `0.10` for 10% slower.
+    pub change: Option<f64>,
+    /// Largest relative change tolerated for this benchmark.
+    pub limit: Option<f64>,
+    pub verdict: Verdict,
+}
+
+impl Gate {
+    /// Checks every benchmark of `baseline` against `current`, where the ones
+    /// `current` lacks are [`Verdict::Missing`].
+    pub fn check(&self, baseline: &[BenchResult], current: &[BenchResult]) -> Vec<Check> {
+        baseline
+            .iter()
+            .map(|base| match current.iter().find(|new| new.id == base.id) {
+                Some(new) => self.check_one(base, new),
+                None => Check {
+                    id: base.id.clone(),
+                    baseline_ns: base.mean.point_estimate,
+                    current_ns: None,
+                    change: None,
+                    limit: None,
+                    verdict: Verdict::Missing,
+                },
+            })
+            .collect()
+    }
+
+    /// Checks one benchmark measured in both runs.
+    pub fn check_one(&self, base: &BenchResult, new: &BenchResult) -> Check {
+        let base_mean = base.mean.point_estimate;
+        let new_mean = new.mean.point_estimate;
+        let noise = f64::max(
+            base.std_dev.point_estimate / base_mean,
+            new.std_dev.point_estimate / new_mean,
+        );
+        let change = new_mean / base_mean - 1.0;
+        let limit = self.threshold + self.noise_factor * noise;
+
+        let base_ci = &base.mean.confidence_interval;
+        let new_ci = &new.mean.confidence_interval;
+        let verdict = if change > limit && new_ci.lower_bound > base_ci.upper_bound {
+            Verdict::Regressed
+        } else if change < -limit && new_ci.upper_bound < base_ci.lower_bound {
+            Verdict::Improved
+        } else {
+            Verdict::Unchanged
+        };
+
+        Check {
+            id: new.id.clone(),
+            baseline_ns: base_mean,
+            current_ns: Some(new_mean),
+            change: Some(change),
+            limit: Some(limit),
+            verdict,
+        }
+    }
+}
+
+/// Renders the checks as a Markdown table, regressions and missing
+/// benchmarks first.
+pub fn markdown(checks: &[Check]) -> String {
+    let mut checks = checks.iter().collect::<Vec<_>>();
+    checks.sort_by_key(|check| match check.verdict {
+        Verdict::Regressed => 0,
+        Verdict::Missing => 1,
+        Verdict::Improved => 2,
+        Verdict::Unchanged => 3,
+    });
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "| Benchmark | Baseline | Current | Change | Limit | Verdict |"
+    );
+    let _ = writeln!(out, "|---|---:|---:|---:|---:|---|");
+    for check in checks {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {:?} |",
+            check.id,
+            format_ns(check.baseline_ns),
+            check.current_ns.map_or("-".to_string(), format_ns),
+            check
+                .change
+                .map_or("-".to_string(), |change| format!("{:+.1}%", change * 100.0)),
+            check
+                .limit
+                .map_or("-".to_string(), |limit| format!("±{:.1}%", limit * 100.0)),
+            check.verdict
+        );
+    }
+    out
+}
@@ -0,0 +1,124 @@
+//! Glob patterns over benchmark ids (`group/function/value`).
+//!
+//! `*` matches any run of characters, `/` included, and `?` matches exactly
+//! one character. Everything else matches itself. A pattern selects an id when
+//! it matches the id or one of its `/`-separated prefixes, so `tokio-find`
+//! selects the whole group and `collections-find/Vec Find` a whole function.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    source: String,
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Any,
+    One,
+    Char(char),
+}
+
+impl Pattern {
+    pub fn new(source: &str) -> Self {
+        let tokens = source
+            .chars()
+            .map(|c| match c {
+                '*' => Token::Any,
+                '?' => Token::One,
+                c => Token::Char(c),
+            })
+            .collect();
+        Self {
+            source: source.to_string(),
+            tokens,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the whole of `text` matches the pattern.
+    pub fn matches(&self, text: &str) -> bool {
+        self.run(text).contains(&self.tokens.len())
+    }
+
+    /// Whether the pattern matches `id` or one of its `/`-separated prefixes.
+    pub fn selects(&self, id: &str) -> bool {
+        self.matches(id)
+            || id
+                .match_indices('/')
+                .any(|(end, _)| self.matches(&id[..end]))
+    }
+
+    /// Whether some string starting with `prefix` could match the pattern,
+    /// e.g. `tokio-*/arc_mutex/*` could match ids under `tokio-find/`.
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        !self.run(prefix).is_empty()
+    }
+
+    /// A regex selecting the same ids as [`Pattern::selects`], as accepted by
+    /// Criterion's filter.
+    pub fn to_regex(&self) -> String {
+        let mut regex = String::from("^");
+        for token in &self.tokens {
+            match token {
+                Token::Any => regex.push_str(".*"),
+                Token::One => regex.push('.'),
+                Token::Char(c) => {
+                    if "\\.+*?()|[]{}^$".contains(*c) {
+                        regex.push('\\');
+                    }
+                    regex.push(*c);
+                }
+            }
+        }
+        regex.push_str("(?:/.*)?$");
+        regex
+    }
+
+    /// Positions in the pattern reachable after consuming all of `text`.
+    fn run(&self, text: &str) -> Vec<usize> {
+        let mut states = self.closure(vec![0]);
+        for c in text.chars() {
+            let next = states
+                .iter()
+                .filter_map(|&i| match self.tokens.get(i)? {
+                    Token::Any => Some(i),
+                    Token::One => Some(i + 1),
+                    Token::Char(p) => (*p == c).then_some(i + 1),
+                })
+                .collect();
+            states = self.closure(next);
+            if states.is_empty() {
+                break;
+            }
+        }
+        states
+    }
+
+    /// Adds the positions reachable by letting `*` match nothing.
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < states.len() {
+            let state = states[i];
+            if self.tokens.get(state) == Some(&Token::Any) && !states.contains(&(state + 1)) {
+                states.push(state + 1);
+            }
+            i += 1;
+        }
+        states.sort_unstable();
+        states.dedup();
+        states
+    }
+}
+
+/// Builds a single Criterion filter matching any of `patterns`.
+pub fn filter_regex(patterns: &[Pattern]) -> String {
+    let alternatives = patterns
+        .iter()
+        .map(Pattern::to_regex)
+        .collect::<Vec<_>>()
+        .join("|");
+    format!("(?:{alternatives})")
+}
@@ -0,0 +1,613 @@
+//! Registry of the benchmark groups, the `[[bench]]` targets defining them,
+//! the dimensions of their parameters and the work each benchmark does.
+
+use crate::{
+    complexity::{Expectation, Model},
+    config,
+    hwinfo::HardwareProfile,
+    params::{Dim, Params, ParseParamsError},
+    profile::Profile,
+    workload::{KeyDistribution, KeyPosition, LookupPattern},
+};
+use criterion::{BenchmarkId, Throughput};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Group {
+    /// Name passed to `Criterion::benchmark_group`.
+    pub name: &'static str,
+    /// `[[bench]]` target registering the group.
+    pub target: &'static str,
+    /// Dimensions of every benchmark parameter in the group.
+    pub dims: &'static [Dim],
+    /// Work one iteration does at `params`, the same for every function.
+    pub work: fn(params: &Params) -> Option<Work>,
+    /// Unit of the Criterion throughput the group reports.
+    pub unit: Unit,
+    /// Whether each benchmark also records performance counters, see
+    /// [`crate::perf`].
+    pub counters: bool,
+    /// Whether benchmarks time their individual operations, see
+    /// [`crate::latency`].
+    pub latency: bool,
+    /// The model each implementation's time should follow along size
+    /// dimensions, see [`crate::complexity`].
+    pub complexity: &'static [Expectation],
+}
+
+/// Work one iteration of a benchmark does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Work {
+    /// Operations, e.g. lookups, increments or requests.
+    pub elements: u64,
+    /// Bytes read or written, when meaningful.
+    pub bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Elements,
+    Bytes,
+}
+
+/// Id of one benchmark: its group, function and parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchId {
+    pub group: &'static str,
+    pub function: String,
+    pub params: Params,
+}
+
+impl BenchId {
+    /// The id to register the benchmark under in its Criterion group.
+    pub fn criterion(&self) -> BenchmarkId {
+        BenchmarkId::new(&self.function, &self.params)
+    }
+}
+
+/// The full id Criterion reports, `group/function/value`.
+impl fmt::Display for BenchId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.group, self.function, self.params)
+    }
+}
+
+impl Group {
+    /// Id of the benchmark of `function` at `params`.
+    pub fn id(&self, function: &str, params: &Params) -> BenchId {
+        BenchId {
+            group: self.name,
+            function: function.to_string(),
+            params: params.clone(),
+        }
+    }
+
+    /// Parameters for the leading `values` of the group's dimensions.
+    pub fn params(&self, values: &[usize]) -> Params {
+        Params::new(self.dims, values)
+    }
+
+    pub fn has_dim(&self, dim: &str) -> bool {
+        self.dims.iter().any(|d| d.name == dim)
+    }
+
+    /// Values to sweep along `dim`: those configured in `napkin.toml` or
+    /// `NAPKIN_SET` (see [`config`]), else `default` as thinned by the current
+    /// [`Profile`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group has no dimension `dim`.
+    pub fn sweep(&self, dim: &str, default: &[usize]) -> Vec<usize> {
+        assert!(self.has_dim(dim), "{} has no dimension `{dim}`", self.name);
+        match config::matrix().get(self.name, dim) {
+            Some(values) => values.to_vec(),
+            None => Profile::current().thin(default),
+        }
+    }
+
+    /// Whether `dim` has values configured in `napkin.toml` or `NAPKIN_SET`
+    /// rather than swept by default.
+    pub fn configured(&self, dim: &str) -> bool {
+        config::matrix().get(self.name, dim).is_some()
+    }
+
+    /// Payload sizes in bytes to sweep along `payload_kb`: those configured,
+    /// else sizes around each cache level of this machine, small enough for a
+    /// collection of `max(lengths)` payloads to fit in half of its memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group has no dimension `payload_kb`.
+    pub fn payload_sizes(&self, lengths: &[usize]) -> Vec<usize> {
+        let profile = HardwareProfile::detect();
+        let max_payloads = lengths.iter().copied().max().unwrap_or(1).max(1);
+        let default_sizes = profile
+            .cache_sweep(profile.memory_budget(2) / max_payloads)
+            .unwrap_or_else(|| {
+                vec![
+                    8 * 1024,         // 8 KB
+                    512 * 1024,       // 512 KB
+                    16 * 1024 * 1024, // 16 MB
+                ]
+            });
+        let default_kb = default_sizes
+            .iter()
+            .map(|size| size / 1024)
+            .collect::<Vec<_>>();
+        self.sweep("payload_kb", &default_kb)
+            .into_iter()
+            .map(|kb| kb * 1024)
+            .collect()
+    }
+
+    /// Parses a parameter of this group back into named dimensions.
+    pub fn parse(&self, text: &str) -> Result<Params, ParseParamsError> {
+        Params::parse(self.dims, text)
+    }
+
+    /// The Criterion throughput of one iteration at `params`, in the group's
+    /// unit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group declares no work for `params`.
+    pub fn throughput(&self, params: &Params) -> Throughput {
+        let work = (self.work)(params)
+            .unwrap_or_else(|| panic!("{} declares no work for `{params}`", self.name));
+        match (self.unit, work.bytes) {
+            (Unit::Bytes, Some(bytes)) => Throughput::Bytes(bytes),
+            _ => Throughput::Elements(work.elements),
+        }
+    }
+}
+
+/// Stride, in bytes, of `memory-access`'s `Stride` benchmark.
+pub const MEMORY_ACCESS_STRIDE: usize = 64;
+
+/// Keys `collections-find` looks up in one iteration.
+pub const COLLECTIONS_FIND_LOOKUPS: usize = 16;
+
+/// Keys `collections-lookups` looks up in one iteration.
+pub const COLLECTIONS_LOOKUPS_BATCH: usize = 1024;
+
+/// Bytes of the payload stored under each key of `collections-lookups`,
+/// `collections-insert`, `collections-remove` and `collections-update`.
+pub const COLLECTIONS_PAYLOAD: usize = 64;
+
+/// Bytes of the payload `tokio-find` rewrites on every request.
+pub const TOKIO_FIND_PAYLOAD: usize = 100;
+
+pub const COLLECTIONS_FIND: Group = Group {
+    name: "collections-find",
+    target: "collections-find",
+    dims: &[
+        Dim {
+            name: "payload_kb",
+            prefix: "",
+        },
+        Dim {
+            name: "length",
+            prefix: "KBx",
+        },
+        Dim {
+            name: "hit_pct",
+            prefix: "h",
+        },
+        // a `workload::KeyPosition`
+        Dim {
+            name: "position",
+            prefix: "p",
+        },
+        // a `workload::KeyDistribution`
+        Dim {
+            name: "keys",
+            prefix: "k",
+        },
+    ],
+    // lookups only compare keys
+    work: |_| {
+        Some(Work {
+            elements: COLLECTIONS_FIND_LOOKUPS as u64,
+            bytes: None,
+        })
+    },
+    unit: Unit::Elements,
+    counters: true,
+    latency: false,
+    complexity: &[
+        Expectation {
+            dim: "length",
+            functions: "BTreeMap Find",
+            model: Model::Log,
+            when: &[],
+        },
+        // every key probes the same bucket
+        Expectation {
+            dim: "length",
+            functions: "FxHashMap Find",
+            model: Model::Linear,
+            when: &[("keys", KeyDistribution::FxCollisions as usize)],
+        },
+        Expectation {
+            dim: "length",
+            functions: "*Map Find",
+            model: Model::Constant,
+            when: &[],
+        },
+        // Vec, VecDeque and SmallVec scan for the key, and only find it right
+        // away when every lookup targets the first one
+        Expectation {
+            dim: "length",
+            functions: "*",
+            model: Model::Constant,
+            when: &[("hit_pct", 100), ("position", KeyPosition::Front as usize)],
+        },
+        Expectation {
+            dim: "length",
+            functions: "*",
+            model: Model::Linear,
+            when: &[],
+        },
+    ],
+};
+
+pub const COLLECTIONS_LOOKUPS: Group = Group {
+    name: "collections-lookups",
+    target: "collections-lookups",
+    dims: &[
+        Dim {
+            name: "length",
+            prefix: "",
+        },
+        // a `workload::LookupPattern`
+        Dim {
+            name: "pattern",
+            prefix: "a",
+        },
+        // the Zipf exponent in hundredths, 0 for the other patterns
+        Dim {
+            name: "skew_pct",
+            prefix: "s",
+        },
+    ],
+    // lookups only compare keys
+    work: |_| {
+        Some(Work {
+            elements: COLLECTIONS_LOOKUPS_BATCH as u64,
+            bytes: None,
+        })
+    },
+    unit: Unit::Elements,
+    counters: true,
+    latency: false,
+    complexity: &[
+        Expectation {
+            dim: "length",
+            functions: "BTreeMap Find",
+            model: Model::Log,
+            when: &[],
+        },
+        Expectation {
+            dim: "length",
+            functions: "*Map Find",
+            model: Model::Constant,
+            when: &[],
+        },
+        // Vec, VecDeque and SmallVec scan for every key
+        Expectation {
+            dim: "length",
+            functions: "*",
+            model: Model::Linear,
+            when: &[],
+        },
+    ],
+};
+
+pub const COLLECTIONS_ITERATE: Group = Group {
+    name: "collections-iterate",
+    target: "collections-iterate",
+    dims: &[
+        Dim {
+            name: "payload_kb",
+            prefix: "",
+        },
+        Dim {
+            name: "length",
+            prefix: "KBx",
+        },
+    ],
+    // one visit per entry
+    work: |params| {
+        Some(Work {
+            elements: params.get("length")? as u64,
+            bytes: None,
+        })
+    },
+    unit: Unit::Elements,
+    counters: true,
+    latency: false,
+    complexity: &[Expectation {
+        dim: "length",
+        functions: "*",
+        model: Model::Linear,
+        when: &[],
+    }],
+};
+
+pub const COLLECTIONS_SUM: Group = Group {
+    name: "collections-sum",
+    target: "collections-sum",
+    dims: COLLECTIONS_ITERATE.dims,
+    // one visit per entry, reading every byte of its payload
+    work: |params| {
+        let length = params.get("length")? as u64;
+        Some(Work {
+            elements: length,
+            bytes: Some(length * params.get("payload_kb")? as u64 * 1024),
+        })
+    },
+    unit: Unit::Bytes,
+    counters: true,
+    latency: false,
+    complexity: COLLECTIONS_ITERATE.complexity,
+};
+
+/// Dimensions of the groups that insert, remove or update every key once.
+const COLLECTIONS_CHURN_DIMS: &[Dim] = &[
+    Dim {
+        name: "length",
+        prefix: "",
+    },
+    // 1 when the collection was created with `with_capacity(length)`, 0 when
+    // it grew from empty
+    Dim {
+        name: "presized",
+        prefix: "p",
+    },
+];
+
+/// One operation per key.
+fn collections_churn_work(params: &Params) -> Option<Work> {
+    Some(Work {
+        elements: params.get("length")? as u64,
+        bytes: None,
+    })
+}
+
+pub const COLLECTIONS_INSERT: Group = Group {
+    name: "collections-insert",
+    target: "collections-insert",
+    dims: COLLECTIONS_CHURN_DIMS,
+    work: collections_churn_work,
+    unit: Unit::Elements,
+    counters: true,
+    latency: false,
+    complexity: &[
+        Expectation {
+            dim: "length",
+            functions: "BTreeMap Insert",
+            model: Model::NLogN,
+            when: &[],
+        },
+        // sequences append, and maps grow geometrically
+        Expectation {
+            dim: "length",
+            functions: "*",
+            model: Model::Linear,
+            when: &[],
+        },
+    ],
+};
+
+pub const COLLECTIONS_REMOVE: Group = Group {
+    name: "collections-remove",
+    target: "collections-remove",
+    dims: COLLECTIONS_CHURN_DIMS,
+    work: collections_churn_work,
+    unit: Unit::Elements,
+    counters: true,
+    latency: false,
+    complexity: &[
+        Expectation {
+            dim: "length",
+            functions: "BTreeMap Remove",
+            model: Model::NLogN,
+            when: &[],
+        },
+        Expectation {
+            dim: "length",
+            functions: "IndexMap SwapRemove",
+            model: Model::Linear,
+            when: &[],
+        },
+        // `shift_remove` shifts every later entry down
+        Expectation {
+            dim: "length",
+            functions: "IndexMap Remove",
+            model: Model::Quadratic,
+            when: &[],
+        },
+        Expectation {
+            dim: "length",
+            functions: "*Map Remove",
+            model: Model::Linear,
+            when: &[],
+        },
+        // Vec, VecDeque and SmallVec scan for every key
+        Expectation {
+            dim: "length",
+            functions: "*",
+            model: Model::Quadratic,
+            when: &[],
+        },
+    ],
+};
+
+pub const COLLECTIONS_UPDATE: Group = Group {
+    name: "collections-update",
+    target: "collections-update",
+    dims: COLLECTIONS_CHURN_DIMS,
+    work: collections_churn_work,
+    unit: Unit::Elements,
+    counters: true,
+    latency: false,
+    complexity: &[
+        Expectation {
+            dim: "length",
+            functions: "BTreeMap Update",
+            model: Model::NLogN,
+            when: &[],
+        },
+        Expectation {
+            dim: "length",
+            functions: "*Map Update",
+            model: Model::Linear,
+            when: &[],
+        },
+        // Vec, VecDeque and SmallVec scan for every key
+        Expectation {
+            dim: "length",
+            functions: "*",
+            model: Model::Quadratic,
+            when: &[],
+        },
+    ],
+};
+
+pub const MEMORY_ACCESS: Group = Group {
+    name: "memory-access",
+    target: "memory-access",
+    dims: &[Dim {
+        name: "size_bytes",
+        prefix: "",
+    }],
+    // every access pattern pulls the whole buffer through the caches: one
+    // element per byte, even though `Stride` only writes one byte per line
+    work: |params| {
+        let size = params.get("size_bytes")? as u64;
+        Some(Work {
+            elements: size,
+            bytes: Some(size),
+        })
+    },
+    unit: Unit::Bytes,
+    counters: true,
+    latency: false,
+    complexity: &[Expectation {
+        dim: "size_bytes",
+        functions: "*",
+        model: Model::Linear,
+        when: &[],
+    }],
+};
+
+pub const TOKIO_INCREMENT: Group = Group {
+    name: "tokio-increment",
+    target: "tokio-increment",
+    dims: &[
+        Dim {
+            name: "tasks",
+            prefix: "",
+        },
+        Dim {
+            name: "increments",
+            prefix: "t",
+        },
+        Dim {
+            name: "capacity",
+            prefix: "c",
+        },
+    ],
+    work: |params| {
+        Some(Work {
+            elements: (params.get("tasks")? * params.get("increments")?) as u64,
+            bytes: None,
+        })
+    },
+    unit: Unit::Elements,
+    counters: false,
+    latency: true,
+    complexity: &[],
+};
+
+pub const TOKIO_FIND: Group = Group {
+    name: "tokio-find",
+    target: "tokio-find",
+    dims: &[
+        Dim {
+            name: "tasks",
+            prefix: "",
+        },
+        Dim {
+            name: "indices",
+            prefix: "t",
+        },
+        Dim {
+            name: "capacity",
+            prefix: "c",
+        },
+    ],
+    // every task looks up and overwrites the payload of every index once
+    work: |params| {
+        let requests = (params.get("tasks")? * params.get("indices")?) as u64;
+        Some(Work {
+            elements: requests,
+            bytes: Some(requests * TOKIO_FIND_PAYLOAD as u64),
+        })
+    },
+    unit: Unit::Elements,
+    counters: false,
+    latency: true,
+    complexity: &[
+        Expectation {
+            dim: "tasks",
+            functions: "*",
+            model: Model::Linear,
+            when: &[],
+        },
+        Expectation {
+            dim: "indices",
+            functions: "dashmap",
+            model: Model::Linear,
+            when: &[],
+        },
+        // every request scans the entries for its index
+        Expectation {
+            dim: "indices",
+            functions: "*",
+            model: Model::Quadratic,
+            when: &[],
+        },
+    ],
+};
+
+pub const GROUPS: &[Group] = &[
+    COLLECTIONS_FIND,
+    COLLECTIONS_LOOKUPS,
+    COLLECTIONS_INSERT,
+    COLLECTIONS_REMOVE,
+    COLLECTIONS_UPDATE,
+    COLLECTIONS_ITERATE,
+    COLLECTIONS_SUM,
+    MEMORY_ACCESS,
+    TOKIO_INCREMENT,
+    TOKIO_FIND,
+];
+
+/// Name of `value` along `dim` when the dimension enumerates variants, e.g.
+/// `fx-collisions` for `keys` 4, so reports and exports need not be decoded
+/// against the source.
+pub fn value_name(dim: &str, value: usize) -> Option<String> {
+    match dim {
+        "position" => KeyPosition::from_value(value).map(|v| v.to_string()),
+        "keys" => KeyDistribution::from_value(value).map(|v| v.to_string()),
+        "pattern" => LookupPattern::from_value(value).map(|v| v.to_string()),
+        _ => None,
+    }
+}
+
+/// Looks a group up by name.
+pub fn find(name: &str) -> Option<&'static Group> {
+    GROUPS.iter().find(|group| group.name == name)
+}
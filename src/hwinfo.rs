@@ -0,0 +1,307 @@
+//! Hardware profile of the machine running the benches.
+//!
+//! On Linux the profile is read from `/proc/cpuinfo`, `/proc/meminfo` and
+//! `/sys/devices/system/cpu`. On other systems only `os` and `arch` are filled
+//! in and every other field is left empty.
+
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// File name of the profile written next to the Criterion output.
+pub const PROFILE_FILE: &str = "hwinfo.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HardwareProfile {
+    pub os: String,
+    pub arch: String,
+    pub vendor: Option<String>,
+    pub model_name: Option<String>,
+    pub logical_cpus: usize,
+    /// Logical CPUs grouped by physical core (SMT siblings).
+    pub cores: Vec<Vec<usize>>,
+    /// Logical CPUs grouped by core cluster, i.e. CPUs sharing an L2.
+    pub clusters: Vec<Vec<usize>>,
+    /// Distinct caches, sorted by level then type.
+    pub caches: Vec<Cache>,
+    pub memory_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cache {
+    pub level: u8,
+    /// `Data`, `Instruction` or `Unified`.
+    pub kind: String,
+    pub size: usize,
+    pub line_size: Option<usize>,
+    pub ways: Option<usize>,
+    pub shared_cpus: Vec<usize>,
+}
+
+impl HardwareProfile {
+    /// Detects the profile of the current machine.
+    pub fn detect() -> Self {
+        Self::from_root(Path::new("/"))
+    }
+
+    /// Reads the profile from a filesystem rooted at `root`, which lets a
+    /// profile be rebuilt from a copied `/proc` and `/sys` tree.
+    pub fn from_root(root: &Path) -> Self {
+        let mut profile = HardwareProfile {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            ..Default::default()
+        };
+
+        if let Ok(cpuinfo) = fs::read_to_string(root.join("proc/cpuinfo")) {
+            profile.vendor = cpuinfo_field(&cpuinfo, &["vendor_id", "CPU implementer"]);
+            profile.model_name = cpuinfo_field(&cpuinfo, &["model name", "Model", "Hardware"]);
+        }
+
+        if let Ok(meminfo) = fs::read_to_string(root.join("proc/meminfo")) {
+            profile.memory_bytes = meminfo
+                .lines()
+                .find_map(|line| line.strip_prefix("MemTotal:"))
+                .and_then(|rest| {
+                    rest.trim()
+                        .trim_end_matches("kB")
+                        .trim()
+                        .parse::<u64>()
+                        .ok()
+                })
+                .map(|kb| kb * 1024);
+        }
+
+        let cpu_root = root.join("sys/devices/system/cpu");
+        let cpus = fs::read_to_string(cpu_root.join("online"))
+            .map(|list| parse_cpu_list(&list))
+            .unwrap_or_default();
+        profile.logical_cpus = cpus.len();
+
+        let mut cores = BTreeSet::new();
+        let mut clusters = BTreeSet::new();
+        let mut caches: Vec<Cache> = Vec::new();
+
+        for &cpu in &cpus {
+            let cpu_dir = cpu_root.join(format!("cpu{cpu}"));
+            let topology = cpu_dir.join("topology");
+
+            if let Some(siblings) = read_cpu_list(&topology.join("thread_siblings_list")) {
+                cores.insert(siblings);
+            }
+
+            let mut l2 = None;
+            for index in read_indexed_dirs(&cpu_dir.join("cache"), "index") {
+                let Some(cache) = read_cache(&index) else {
+                    continue;
+                };
+                if cache.level == 2 {
+                    l2 = Some(cache.shared_cpus.clone());
+                }
+                // the same cache is listed once per CPU sharing it
+                if !caches.contains(&cache) {
+                    caches.push(cache);
+                }
+            }
+
+            if let Some(cluster) = read_cpu_list(&topology.join("cluster_cpus_list")).or(l2) {
+                clusters.insert(cluster);
+            }
+        }
+
+        profile.cores = cores.into_iter().collect();
+        profile.clusters = clusters.into_iter().collect();
+        caches.sort_by(|a, b| {
+            (a.level, &a.kind, &a.shared_cpus).cmp(&(b.level, &b.kind, &b.shared_cpus))
+        });
+        profile.caches = caches;
+        profile
+    }
+
+    /// Returns the first cache of `level` that holds data (`Data` or
+    /// `Unified`).
+    pub fn data_cache(&self, level: u8) -> Option<&Cache> {
+        self.caches
+            .iter()
+            .find(|c| c.level == level && c.kind != "Instruction")
+    }
+
+    /// Buffer sizes straddling every data cache level: half of, equal to and
+    /// double the L1d, L2 and L3 sizes, dropping anything above `max`.
+    ///
+    /// Returns `None` when no data cache was detected, so callers can fall
+    /// back to a fixed sweep.
+    pub fn cache_sweep(&self, max: usize) -> Option<Vec<usize>> {
+        let mut sizes = (1..=3)
+            .filter_map(|level| self.data_cache(level))
+            .flat_map(|cache| [cache.size / 2, cache.size, cache.size * 2])
+            .filter(|&size| size > 0 && size <= max)
+            .collect::<Vec<_>>();
+        if sizes.is_empty() {
+            return None;
+        }
+        sizes.sort_unstable();
+        sizes.dedup();
+        Some(sizes)
+    }
+
+    /// A share of total memory a bench may allocate, `1 / divisor` of it, or
+    /// 1 GB when the total is unknown.
+    pub fn memory_budget(&self, divisor: usize) -> usize {
+        self.memory_bytes
+            .map_or(1024 * 1024 * 1024, |bytes| bytes as usize / divisor)
+    }
+
+    /// Stable identifier of the machine: a slug of the CPU model followed by a
+    /// hash of the OS, architecture, CPU, topology, caches and memory (rounded
+    /// to whole GB), e.g. `amd-ryzen-7-7700x-8-core-processor-6f1c9a2e`.
+    pub fn fingerprint(&self) -> String {
+        let caches = self
+            .caches
+            .iter()
+            .map(|c| format!("L{}{}:{}x{}", c.level, c.kind, c.size, c.shared_cpus.len()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let memory_gb = self.memory_bytes.map(|b| (b + (1 << 29)) >> 30);
+        let identity = format!(
+            "{}|{}|{:?}|{:?}|{}|{}|{}|{caches}|{memory_gb:?}",
+            self.os,
+            self.arch,
+            self.vendor,
+            self.model_name,
+            self.logical_cpus,
+            self.cores.len(),
+            self.clusters.len(),
+        );
+
+        let model = self.model_name.as_deref().unwrap_or(&self.arch);
+        let mut slug = String::new();
+        for c in model.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let slug = slug.trim_end_matches('-');
+        format!("{slug}-{:08x}", fnv1a(identity.as_bytes()) as u32)
+    }
+
+    /// Writes the profile as pretty JSON to `path`, creating parent
+    /// directories as needed.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    /// Reads a profile previously written with [`HardwareProfile::write`].
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(io::Error::other)
+    }
+}
+
+/// Detects the current machine and writes its profile next to the Criterion
+/// output. Every bench target calls this before registering its groups.
+pub fn record() -> io::Result<PathBuf> {
+    let path = paths::criterion_home().join(PROFILE_FILE);
+    HardwareProfile::detect().write(&path)?;
+    Ok(path)
+}
+
+/// 64-bit FNV-1a, stable across Rust releases unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn cpuinfo_field(cpuinfo: &str, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| {
+        cpuinfo.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == *name).then(|| value.trim().to_string())
+        })
+    })
+}
+
+fn read_cache(dir: &Path) -> Option<Cache> {
+    let read = |name: &str| fs::read_to_string(dir.join(name)).ok();
+    let number = |name: &str| read(name).and_then(|v| v.trim().parse::<usize>().ok());
+
+    Some(Cache {
+        level: read("level")?.trim().parse().ok()?,
+        kind: read("type")?.trim().to_string(),
+        size: parse_size(&read("size")?)?,
+        line_size: number("coherency_line_size"),
+        ways: number("ways_of_associativity"),
+        shared_cpus: read_cpu_list(&dir.join("shared_cpu_list")).unwrap_or_default(),
+    })
+}
+
+/// Lists `<dir>/<prefix>N` directories in ascending `N`.
+fn read_indexed_dirs(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let index = path
+                .file_name()?
+                .to_str()?
+                .strip_prefix(prefix)?
+                .parse::<usize>()
+                .ok()?;
+            Some((index, path))
+        })
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.into_iter().map(|(_, path)| path).collect()
+}
+
+fn read_cpu_list(path: &Path) -> Option<Vec<usize>> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|list| parse_cpu_list(&list))
+}
+
+/// Parses the kernel's CPU list format, e.g. `0-3,8,10-11`.
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((lo, hi)) => {
+                if let (Ok(lo), Ok(hi)) = (lo.parse::<usize>(), hi.parse::<usize>()) {
+                    cpus.extend(lo..=hi);
+                }
+            }
+            None => cpus.extend(part.parse::<usize>().ok()),
+        }
+    }
+    cpus
+}
+
+/// Parses sysfs cache sizes such as `48K`, `2048K` or `32M`.
+pub fn parse_size(size: &str) -> Option<usize> {
+    let size = size.trim();
+    let (digits, unit) = match size.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => size.split_at(i),
+        None => (size, ""),
+    };
+    let multiplier = match unit {
+        "" => 1,
+        "K" => 1024,
+        "M" => 1024 * 1024,
+        "G" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some(digits.parse::<usize>().ok()? * multiplier)
+}
@@ -0,0 +1,424 @@
+//! The collections the `collections-*` groups compare, behind one
+//! [`BenchCollection`] trait. Every collection maps `usize` keys to a
+//! [`Payload`]; sequences store `(key, payload)` pairs and scan for keys.
+//!
+//! A group benchmarks every collection by implementing [`Visitor`] and
+//! passing it to [`visit_all`], which `collections!` generates from the same
+//! list that declares the impls, so a new collection only needs its impl.
+
+use crate::workload::Payload;
+use dashmap::DashMap;
+use indexmap::IndexMap;
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// One key and its payload, as sequences store them.
+pub type Entry = (usize, Payload);
+
+pub trait BenchCollection: Sized {
+    /// Name in benchmark ids, e.g. `SmallVec<8>`.
+    const NAME: &'static str;
+
+    /// Whether [`BenchCollection::swap_remove`] differs from
+    /// [`BenchCollection::remove`].
+    const SWAP_REMOVE: bool = false;
+
+    fn build(entries: impl IntoIterator<Item = Entry>) -> Self;
+
+    /// An empty collection with room for `capacity` entries, where the
+    /// collection can reserve it.
+    fn with_capacity(capacity: usize) -> Self;
+
+    /// Length of the payload stored under `key`, `None` when it is absent.
+    fn find(&self, key: usize) -> Option<usize>;
+
+    /// Adds an entry. `key` must be absent: sequences append without
+    /// scanning for it.
+    fn insert(&mut self, key: usize, payload: Payload);
+
+    /// Removes the entry of `key`, keeping the order of the others in the
+    /// collections that have one.
+    fn remove(&mut self, key: usize) -> Option<Payload>;
+
+    /// Removes the entry of `key`, moving the last entry into its place in
+    /// the collections that have an order.
+    fn swap_remove(&mut self, key: usize) -> Option<Payload> {
+        self.remove(key)
+    }
+
+    /// Calls `f` on the payload of `key` in place, returning whether it was
+    /// present.
+    fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool;
+
+    /// Calls `f` on every entry, in the collection's order.
+    fn for_each(&self, f: impl FnMut(usize, &Payload));
+}
+
+/// Something done with every collection, e.g. registering its benchmarks.
+pub trait Visitor {
+    fn visit<C: BenchCollection>(&mut self);
+}
+
+/// Sums every key and payload length, reading only what the collection
+/// stores itself, as `collections-iterate`'s `Visit` does.
+pub fn sum_keys<C: BenchCollection>(collection: &C) -> usize {
+    let mut sum = 0usize;
+    collection.for_each(|key, payload| sum = sum.wrapping_add(key).wrapping_add(payload.len()));
+    sum
+}
+
+/// Sums every byte of every payload, as `collections-sum` does.
+pub fn sum_payloads<C: BenchCollection>(collection: &C) -> u64 {
+    let mut sum = 0u64;
+    collection.for_each(|_, payload| {
+        sum += payload.iter().map(|&byte| u64::from(byte)).sum::<u64>();
+    });
+    sum
+}
+
+/// An empty collection, created with room for `capacity` entries when
+/// `presized` and with none otherwise.
+pub fn empty<C: BenchCollection>(capacity: usize, presized: bool) -> C {
+    C::with_capacity(if presized { capacity } else { 0 })
+}
+
+/// A collection filled by inserting `entries` one at a time into
+/// [`empty`], unlike [`BenchCollection::build`] which may size it up front.
+pub fn filled<C: BenchCollection>(entries: &[Entry], presized: bool) -> C {
+    let mut collection = empty::<C>(entries.len(), presized);
+    for (key, payload) in entries {
+        collection.insert(*key, payload.clone());
+    }
+    collection
+}
+/// The [`BenchCollection`] items of a `SmallVec` of `$inline` inline entries.
+macro_rules! smallvec_collection {
+    ($inline:literal) => {
+        const NAME: &'static str = concat!("SmallVec<", $inline, ">");
+        const SWAP_REMOVE: bool = true;
+
+        fn build(entries: impl IntoIterator<Item = Entry>) -> Self {
+            entries.into_iter().collect()
+        }
+
+        fn with_capacity(capacity: usize) -> Self {
+            SmallVec::with_capacity(capacity)
+        }
+
+        fn find(&self, key: usize) -> Option<usize> {
+            self.iter().find(|(k, _)| *k == key).map(|(_, v)| v.len())
+        }
+
+        fn insert(&mut self, key: usize, payload: Payload) {
+            self.push((key, payload));
+        }
+
+        fn remove(&mut self, key: usize) -> Option<Payload> {
+            let at = self.iter().position(|(k, _)| *k == key)?;
+            Some(self.remove(at).1)
+        }
+
+        fn swap_remove(&mut self, key: usize) -> Option<Payload> {
+            let at = self.iter().position(|(k, _)| *k == key)?;
+            Some(self.swap_remove(at).1)
+        }
+
+        fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool {
+            self.iter_mut()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| f(v))
+                .is_some()
+        }
+
+        fn for_each(&self, mut f: impl FnMut(usize, &Payload)) {
+            self.iter().for_each(|(k, v)| f(*k, v));
+        }
+    };
+}
+
+/// Implements [`BenchCollection`] for each collection listed and generates
+/// [`visit_all`] over them in the same order, so a collection is registered
+/// by declaring its impl here.
+macro_rules! collections {
+    ($(impl BenchCollection for $collection:ty { $($item:tt)* })*) => {
+        $(impl BenchCollection for $collection { $($item)* })*
+
+        /// Visits every collection, in the order groups register them.
+        pub fn visit_all(visitor: &mut impl Visitor) {
+            $(visitor.visit::<$collection>();)*
+        }
+    };
+}
+
+collections! {
+    impl BenchCollection for Vec<Entry> {
+        const NAME: &'static str = "Vec";
+        const SWAP_REMOVE: bool = true;
+
+        fn build(entries: impl IntoIterator<Item = Entry>) -> Self {
+            entries.into_iter().collect()
+        }
+
+        fn with_capacity(capacity: usize) -> Self {
+            Vec::with_capacity(capacity)
+        }
+
+        fn find(&self, key: usize) -> Option<usize> {
+            self.iter().find(|(k, _)| *k == key).map(|(_, v)| v.len())
+        }
+
+        fn insert(&mut self, key: usize, payload: Payload) {
+            self.push((key, payload));
+        }
+
+        fn remove(&mut self, key: usize) -> Option<Payload> {
+            let at = self.iter().position(|(k, _)| *k == key)?;
+            Some(self.remove(at).1)
+        }
+
+        fn swap_remove(&mut self, key: usize) -> Option<Payload> {
+            let at = self.iter().position(|(k, _)| *k == key)?;
+            Some(self.swap_remove(at).1)
+        }
+
+        fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool {
+            self.iter_mut()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| f(v))
+                .is_some()
+        }
+
+        fn for_each(&self, mut f: impl FnMut(usize, &Payload)) {
+            self.iter().for_each(|(k, v)| f(*k, v));
+        }
+    }
+
+    impl BenchCollection for HashMap<usize, Payload> {
+        const NAME: &'static str = "HashMap";
+
+        fn build(entries: impl IntoIterator<Item = Entry>) -> Self {
+            entries.into_iter().collect()
+        }
+
+        fn with_capacity(capacity: usize) -> Self {
+            HashMap::with_capacity(capacity)
+        }
+
+        fn find(&self, key: usize) -> Option<usize> {
+            self.get(&key).map(Vec::len)
+        }
+
+        fn insert(&mut self, key: usize, payload: Payload) {
+            HashMap::insert(self, key, payload);
+        }
+
+        fn remove(&mut self, key: usize) -> Option<Payload> {
+            HashMap::remove(self, &key)
+        }
+
+        fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool {
+            self.get_mut(&key).map(f).is_some()
+        }
+
+        fn for_each(&self, mut f: impl FnMut(usize, &Payload)) {
+            self.iter().for_each(|(k, v)| f(*k, v));
+        }
+    }
+
+    impl BenchCollection for BTreeMap<usize, Payload> {
+        const NAME: &'static str = "BTreeMap";
+
+        fn build(entries: impl IntoIterator<Item = Entry>) -> Self {
+            entries.into_iter().collect()
+        }
+
+        /// B-trees allocate node by node, so there is nothing to reserve.
+        fn with_capacity(_: usize) -> Self {
+            BTreeMap::new()
+        }
+
+        fn find(&self, key: usize) -> Option<usize> {
+            self.get(&key).map(Vec::len)
+        }
+
+        fn insert(&mut self, key: usize, payload: Payload) {
+            BTreeMap::insert(self, key, payload);
+        }
+
+        fn remove(&mut self, key: usize) -> Option<Payload> {
+            BTreeMap::remove(self, &key)
+        }
+
+        fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool {
+            self.get_mut(&key).map(f).is_some()
+        }
+
+        fn for_each(&self, mut f: impl FnMut(usize, &Payload)) {
+            self.iter().for_each(|(k, v)| f(*k, v));
+        }
+    }
+
+    impl BenchCollection for DashMap<usize, Payload> {
+        const NAME: &'static str = "DashMap";
+
+        fn build(entries: impl IntoIterator<Item = Entry>) -> Self {
+            entries.into_iter().collect()
+        }
+
+        fn with_capacity(capacity: usize) -> Self {
+            DashMap::with_capacity(capacity)
+        }
+
+        fn find(&self, key: usize) -> Option<usize> {
+            self.get(&key).map(|v| v.len())
+        }
+
+        fn insert(&mut self, key: usize, payload: Payload) {
+            DashMap::insert(self, key, payload);
+        }
+
+        fn remove(&mut self, key: usize) -> Option<Payload> {
+            DashMap::remove(self, &key).map(|(_, v)| v)
+        }
+
+        fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool {
+            self.get_mut(&key).map(|mut v| f(&mut v)).is_some()
+        }
+
+        fn for_each(&self, mut f: impl FnMut(usize, &Payload)) {
+            self.iter().for_each(|entry| f(*entry.key(), entry.value()));
+        }
+    }
+
+    impl BenchCollection for IndexMap<usize, Payload> {
+        const NAME: &'static str = "IndexMap";
+        const SWAP_REMOVE: bool = true;
+
+        fn build(entries: impl IntoIterator<Item = Entry>) -> Self {
+            entries.into_iter().collect()
+        }
+
+        fn with_capacity(capacity: usize) -> Self {
+            IndexMap::with_capacity(capacity)
+        }
+
+        fn find(&self, key: usize) -> Option<usize> {
+            self.get(&key).map(Vec::len)
+        }
+
+        fn insert(&mut self, key: usize, payload: Payload) {
+            IndexMap::insert(self, key, payload);
+        }
+
+        fn remove(&mut self, key: usize) -> Option<Payload> {
+            self.shift_remove(&key)
+        }
+
+        fn swap_remove(&mut self, key: usize) -> Option<Payload> {
+            IndexMap::swap_remove(self, &key)
+        }
+
+        fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool {
+            self.get_mut(&key).map(f).is_some()
+        }
+
+        fn for_each(&self, mut f: impl FnMut(usize, &Payload)) {
+            self.iter().for_each(|(k, v)| f(*k, v));
+        }
+    }
+
+    impl BenchCollection for SmallVec<[Entry; 1]> {
+        smallvec_collection!(1);
+    }
+
+    impl BenchCollection for SmallVec<[Entry; 4]> {
+        smallvec_collection!(4);
+    }
+
+    impl BenchCollection for SmallVec<[Entry; 8]> {
+        smallvec_collection!(8);
+    }
+
+    impl BenchCollection for SmallVec<[Entry; 16]> {
+        smallvec_collection!(16);
+    }
+
+    impl BenchCollection for SmallVec<[Entry; 32]> {
+        smallvec_collection!(32);
+    }
+
+    impl BenchCollection for VecDeque<Entry> {
+        const NAME: &'static str = "VecDeque";
+        const SWAP_REMOVE: bool = true;
+
+        fn build(entries: impl IntoIterator<Item = Entry>) -> Self {
+            entries.into_iter().collect()
+        }
+
+        fn with_capacity(capacity: usize) -> Self {
+            VecDeque::with_capacity(capacity)
+        }
+
+        fn find(&self, key: usize) -> Option<usize> {
+            self.iter().find(|(k, _)| *k == key).map(|(_, v)| v.len())
+        }
+
+        fn insert(&mut self, key: usize, payload: Payload) {
+            self.push_back((key, payload));
+        }
+
+        fn remove(&mut self, key: usize) -> Option<Payload> {
+            let at = self.iter().position(|(k, _)| *k == key)?;
+            self.remove(at).map(|(_, v)| v)
+        }
+
+        fn swap_remove(&mut self, key: usize) -> Option<Payload> {
+            let at = self.iter().position(|(k, _)| *k == key)?;
+            self.swap_remove_back(at).map(|(_, v)| v)
+        }
+
+        fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool {
+            self.iter_mut()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| f(v))
+                .is_some()
+        }
+
+        fn for_each(&self, mut f: impl FnMut(usize, &Payload)) {
+            self.iter().for_each(|(k, v)| f(*k, v));
+        }
+    }
+
+    impl BenchCollection for FxHashMap<usize, Payload> {
+        const NAME: &'static str = "FxHashMap";
+
+        fn build(entries: impl IntoIterator<Item = Entry>) -> Self {
+            entries.into_iter().collect()
+        }
+
+        fn with_capacity(capacity: usize) -> Self {
+            FxHashMap::with_capacity_and_hasher(capacity, Default::default())
+        }
+
+        fn find(&self, key: usize) -> Option<usize> {
+            self.get(&key).map(Vec::len)
+        }
+
+        fn insert(&mut self, key: usize, payload: Payload) {
+            HashMap::insert(self, key, payload);
+        }
+
+        fn remove(&mut self, key: usize) -> Option<Payload> {
+            HashMap::remove(self, &key)
+        }
+
+        fn update(&mut self, key: usize, f: impl FnOnce(&mut Payload)) -> bool {
+            self.get_mut(&key).map(f).is_some()
+        }
+
+        fn for_each(&self, mut f: impl FnMut(usize, &Payload)) {
+            self.iter().for_each(|(k, v)| f(*k, v));
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! Access patterns compared by `memory-access`. Each increments, with
+//! wrapping, every byte it visits.
+
+/// Visits every byte in order.
+pub fn sequential_access(data: &mut [u8]) {
+    for d in data {
+        *d = d.wrapping_add(1);
+    }
+}
+
+/// Visits the bytes at `indices`, in that order.
+pub fn random_access(data: &mut [u8], indices: &[usize]) {
+    for &i in indices {
+        data[i] = data[i].wrapping_add(1);
+    }
+}
+
+/// Visits one byte every `stride` bytes, starting from the first.
+pub fn stride_access(data: &mut [u8], stride: usize) {
+    let len = data.len();
+    for i in (0..len).step_by(stride) {
+        data[i] = data[i].wrapping_add(1);
+    }
+}
@@ -0,0 +1,8 @@
+//! The implementations each group compares, shared by its bench target and
+//! the differential tests in `tests/`, which check that every implementation
+//! of a group produces the same observable result.
+
+pub mod collections;
+pub mod memory_access;
+pub mod tokio_find;
+pub mod tokio_increment;
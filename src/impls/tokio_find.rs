@@ -0,0 +1,188 @@
+//! Request strategies compared by `tokio-find`. Each spawns `num_tasks`
+//! tasks that each look up their `indices_per_task` entries and overwrite
+//! the payload with zeros, then returns the entries.
+
+use crate::{impls::tokio_increment::BROADCAST_CAPACITY, latency::Record, workload::Payload};
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio_stream::{
+    StreamExt,
+    wrappers::{BroadcastStream, ReceiverStream, UnboundedReceiverStream},
+};
+
+pub async fn arc_mutex<R: Record>(
+    num_tasks: usize,
+    mut handles: Vec<tokio::task::JoinHandle<()>>,
+    values: Vec<(usize, Payload)>,
+    mut indices_per_task: Vec<Vec<usize>>,
+) -> Vec<(usize, Payload)> {
+    let counter = Arc::new(Mutex::new(values));
+
+    for _ in 0..num_tasks {
+        let counter = Arc::clone(&counter);
+        let indices = indices_per_task.pop().unwrap();
+        handles.push(tokio::spawn(async move {
+            let mut ops = R::new();
+            // we search each index and we set the data zero
+            for i in indices {
+                let start = ops.start();
+                let mut guard = counter.lock().await;
+                let (_, data) = guard.iter_mut().find(|(index, _)| *index == i).unwrap();
+                let len = data.len();
+                *data = vec![0; len];
+                drop(guard);
+                ops.stop(start);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    Arc::try_unwrap(counter).unwrap().into_inner()
+}
+
+pub async fn unbounded<R: Record>(
+    num_tasks: usize,
+    mut values: Vec<(usize, Payload)>,
+    mut indices_per_task: Vec<Vec<usize>>,
+) -> Vec<(usize, Payload)> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut rx = UnboundedReceiverStream::new(rx);
+
+    for _ in 0..num_tasks {
+        let tx = tx.clone();
+        let indices = indices_per_task.pop().unwrap();
+        tokio::spawn(async move {
+            let mut ops = R::new();
+            for i in indices {
+                let start = ops.start();
+                let (one_tx, one_rx) = tokio::sync::oneshot::channel();
+                tx.send((i, one_tx)).unwrap();
+                one_rx.await.unwrap();
+                ops.stop(start);
+            }
+        });
+    }
+
+    drop(tx); // kill last sender
+
+    while let Some((i, one_tx)) = rx.next().await {
+        let (_, data) = values.iter_mut().find(|(index, _)| *index == i).unwrap();
+        let len = data.len();
+        *data = vec![0; len];
+        one_tx.send(()).unwrap();
+    }
+
+    values
+}
+
+pub async fn mpsc<R: Record>(
+    num_tasks: usize,
+    mut values: Vec<(usize, Payload)>,
+    mut indices_per_task: Vec<Vec<usize>>,
+    capacity: usize,
+) -> Vec<(usize, Payload)> {
+    let (tx, rx) = tokio::sync::mpsc::channel(capacity);
+    let mut rx = ReceiverStream::new(rx);
+
+    for _ in 0..num_tasks {
+        let tx = tx.clone();
+        let indices = indices_per_task.pop().unwrap();
+        tokio::spawn(async move {
+            let mut ops = R::new();
+            for i in indices {
+                let start = ops.start();
+                let (one_tx, one_rx) = tokio::sync::oneshot::channel();
+                tx.send((i, one_tx)).await.ok();
+                one_rx.await.unwrap();
+                ops.stop(start);
+            }
+        });
+    }
+
+    drop(tx); // kill last sender
+
+    while let Some((i, one_tx)) = rx.next().await {
+        let (_, data) = values.iter_mut().find(|(index, _)| *index == i).unwrap();
+        let len = data.len();
+        *data = vec![0; len];
+        one_tx.send(()).unwrap();
+    }
+
+    values
+}
+
+pub async fn broadcast<R: Record>(
+    num_tasks: usize,
+    mut values: Vec<(usize, Payload)>,
+    mut indices_per_task: Vec<Vec<usize>>,
+) -> Vec<(usize, Payload)> {
+    let (tx, rx) = tokio::sync::broadcast::channel(BROADCAST_CAPACITY);
+    let mut rx = BroadcastStream::new(rx);
+
+    for _ in 0..num_tasks {
+        let tx = tx.clone();
+        let indices = indices_per_task.pop().unwrap();
+        tokio::spawn(async move {
+            let mut ops = R::new();
+            for i in indices {
+                let start = ops.start();
+                let (one_tx, mut one_rx) = tokio::sync::mpsc::channel(1);
+                tx.send((i, one_tx)).ok();
+                one_rx.recv().await;
+                ops.stop(start);
+            }
+        });
+    }
+
+    drop(tx); // kill last sender
+
+    while let Some(Ok((i, one_tx))) = rx.next().await {
+        let (_, data) = values.iter_mut().find(|(index, _)| *index == i).unwrap();
+        let len = data.len();
+        *data = vec![0; len];
+        one_tx.send(()).await.unwrap();
+    }
+
+    values
+}
+
+pub async fn dash_map<R: Record>(
+    num_tasks: usize,
+    mut handles: Vec<tokio::task::JoinHandle<()>>,
+    values: Vec<(usize, Payload)>,
+    mut indices_per_task: Vec<Vec<usize>>,
+) -> DashMap<usize, Payload> {
+    let dash = Arc::new(
+        values
+            .iter()
+            .map(|(i, v)| (*i, v.clone()))
+            .collect::<DashMap<_, _>>(),
+    );
+
+    for _ in 0..num_tasks {
+        let indices = indices_per_task.pop().unwrap();
+        let dash = dash.clone();
+        handles.push(tokio::spawn(async move {
+            let mut ops = R::new();
+            // we search each index and we set the data zero
+            for i in indices {
+                let start = ops.start();
+                let mut data = dash.get_mut(&i).unwrap();
+                let len = data.len();
+                *data = vec![0; len];
+                drop(data);
+                ops.stop(start);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    Arc::try_unwrap(dash).unwrap()
+}
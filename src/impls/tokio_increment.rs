@@ -0,0 +1,164 @@
+//! Shared counters compared by `tokio-increment`. Each spawns `num_tasks`
+//! tasks adding `increments_per_task` to a counter, or sending that many
+//! messages to one receiver counting them until every sender is gone, and
+//! returns the final count after asserting it holds every increment.
+
+use crate::latency::Record;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use tokio::sync::Mutex;
+use tokio_stream::{
+    StreamExt,
+    wrappers::{BroadcastStream, ReceiverStream, UnboundedReceiverStream},
+};
+
+/// Capacity of the broadcast channel, large enough that the receiver never
+/// lags behind.
+pub const BROADCAST_CAPACITY: usize = 10_000_000;
+
+pub async fn atomic_usize(
+    num_tasks: usize,
+    increments_per_task: usize,
+    mut handles: Vec<tokio::task::JoinHandle<()>>,
+) -> usize {
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..num_tasks {
+        let counter = counter.clone();
+        handles.push(tokio::spawn(async move {
+            for _ in 0..increments_per_task {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    let counter = counter.load(Ordering::Relaxed);
+    assert_eq!(counter, num_tasks * increments_per_task);
+    counter
+}
+
+pub async fn arc_mutex<R: Record>(
+    num_tasks: usize,
+    increments_per_task: usize,
+    mut handles: Vec<tokio::task::JoinHandle<()>>,
+) -> usize {
+    let counter = Arc::new(Mutex::new(0usize));
+
+    for _ in 0..num_tasks {
+        let counter = Arc::clone(&counter);
+        handles.push(tokio::spawn(async move {
+            let mut ops = R::new();
+            for _ in 0..increments_per_task {
+                let start = ops.start();
+                let mut lock = counter.lock().await;
+                *lock += 1;
+                drop(lock);
+                ops.stop(start);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    let counter = *counter.lock().await;
+    assert_eq!(counter, num_tasks * increments_per_task);
+    counter
+}
+
+pub async fn unbounded<R: Record>(num_tasks: usize, increments_per_task: usize) -> usize {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut rx = UnboundedReceiverStream::new(rx);
+    let mut counter = 0;
+
+    for _ in 0..num_tasks {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut ops = R::new();
+            for _ in 0..increments_per_task {
+                let start = ops.start();
+                tx.send(()).unwrap();
+                ops.stop(start);
+            }
+        });
+    }
+
+    drop(tx); // kill last sender, ending the stream once every task is done
+    while rx.next().await.is_some() {
+        counter += 1;
+    }
+
+    assert_eq!(counter, num_tasks * increments_per_task);
+    counter
+}
+
+pub async fn mpsc<R: Record>(
+    num_tasks: usize,
+    increments_per_task: usize,
+    capacity: usize,
+) -> usize {
+    let (tx, rx) = tokio::sync::mpsc::channel(capacity);
+    let mut rx = ReceiverStream::new(rx);
+    let mut counter = 0;
+
+    for _ in 0..num_tasks {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut ops = R::new();
+            for _ in 0..increments_per_task {
+                let start = ops.start();
+                loop {
+                    if tx.send(()).await.is_ok() {
+                        break;
+                    }
+                }
+                ops.stop(start);
+            }
+        });
+    }
+
+    drop(tx); // kill last sender, ending the stream once every task is done
+    while rx.next().await.is_some() {
+        counter += 1;
+    }
+
+    assert_eq!(counter, num_tasks * increments_per_task);
+    counter
+}
+
+pub async fn broadcast<R: Record>(num_tasks: usize, increments_per_task: usize) -> usize {
+    let (tx, rx) = tokio::sync::broadcast::channel(BROADCAST_CAPACITY);
+    let mut rx = BroadcastStream::new(rx);
+    let mut counter = 0;
+
+    for _ in 0..num_tasks {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut ops = R::new();
+            for _ in 0..increments_per_task {
+                let start = ops.start();
+                loop {
+                    if tx.send(()).is_ok() {
+                        break;
+                    }
+                }
+                ops.stop(start);
+            }
+        });
+    }
+
+    drop(tx); // kill last sender, ending the stream once every task is done
+    while rx.next().await.is_some() {
+        counter += 1;
+    }
+
+    assert_eq!(counter, num_tasks * increments_per_task);
+    counter
+}
@@ -0,0 +1,147 @@
+//! Per-operation latency histograms.
+//!
+//! Criterion reports one mean per iteration, which hides the tail of the
+//! individual sends, lock acquisitions and requests an iteration is made of.
+//! Routines of groups that opt in are generic over [`Record`]. Criterion
+//! times them with [`NoRecorder`], which compiles away, and only the probe's
+//! latency pass runs them with a [`Recorder`] timing each operation. Their
+//! HdrHistograms are merged into [`LatencyStats`] percentiles.
+
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+/// Sidecar kind holding the [`LatencyStats`] of every benchmark.
+pub const SIDECAR: &str = "latency";
+
+/// Significant decimal digits the histograms keep.
+const SIGNIFICANT_DIGITS: u8 = 3;
+
+/// Odd while a pass records, bumped at its start and end. Recorders created
+/// in one pass and dropped after it, by tasks still running when the routine
+/// returned, are discarded.
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+static MERGED: Mutex<Option<Histogram<u64>>> = Mutex::new(None);
+
+/// Latency percentiles of the operations recorded in a pass, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub operations: u64,
+    pub p50_ns: f64,
+    pub p99_ns: f64,
+    pub p999_ns: f64,
+    pub max_ns: f64,
+}
+
+impl LatencyStats {
+    fn from_histogram(histogram: &Histogram<u64>) -> Self {
+        Self {
+            operations: histogram.len(),
+            p50_ns: histogram.value_at_quantile(0.5) as f64,
+            p99_ns: histogram.value_at_quantile(0.99) as f64,
+            p999_ns: histogram.value_at_quantile(0.999) as f64,
+            max_ns: histogram.max() as f64,
+        }
+    }
+}
+
+/// Times the operations of one task. Create one per task, and wrap each
+/// operation in [`Record::start`] and [`Record::stop`].
+pub trait Record: Send + 'static {
+    type Start: Send;
+
+    fn new() -> Self;
+
+    /// Starts timing an operation.
+    fn start(&self) -> Self::Start;
+
+    /// Records the operation started at `start`.
+    fn stop(&mut self, start: Self::Start);
+}
+
+/// Records nothing, for the routines Criterion times.
+pub struct NoRecorder;
+
+impl Record for NoRecorder {
+    type Start = ();
+
+    #[inline(always)]
+    fn new() -> Self {
+        NoRecorder
+    }
+
+    #[inline(always)]
+    fn start(&self) {}
+
+    #[inline(always)]
+    fn stop(&mut self, (): ()) {}
+}
+
+/// Records operations during a latency pass, and does nothing outside one.
+/// Merges what it recorded when dropped.
+pub struct Recorder {
+    epoch: u64,
+    histogram: Option<Histogram<u64>>,
+}
+
+impl Record for Recorder {
+    type Start = Option<Instant>;
+
+    fn new() -> Self {
+        let epoch = EPOCH.load(Ordering::Acquire);
+        let histogram = (epoch % 2 == 1)
+            .then(|| Histogram::new(SIGNIFICANT_DIGITS).expect("invalid histogram precision"));
+        Self { epoch, histogram }
+    }
+
+    fn start(&self) -> Option<Instant> {
+        self.histogram.as_ref().map(|_| Instant::now())
+    }
+
+    fn stop(&mut self, start: Option<Instant>) {
+        if let (Some(histogram), Some(start)) = (&mut self.histogram, start) {
+            let ns = start.elapsed().as_nanos() as u64;
+            histogram.record(ns).expect("histograms auto-resize");
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let Some(histogram) = self.histogram.take() else {
+            return;
+        };
+        let mut merged = MERGED.lock().unwrap();
+        if EPOCH.load(Ordering::Acquire) != self.epoch {
+            return;
+        }
+        match merged.as_mut() {
+            Some(merged) => merged.add(&histogram).expect("histograms auto-resize"),
+            None => *merged = Some(histogram),
+        }
+    }
+}
+
+/// Starts a latency pass: recorders created from now on record.
+pub(crate) fn begin() {
+    let mut merged = MERGED.lock().unwrap();
+    *merged = None;
+    EPOCH.fetch_add(1, Ordering::AcqRel);
+}
+
+/// Ends the latency pass, returning the percentiles of every operation
+/// recorded by the recorders dropped so far, if any.
+pub(crate) fn finish() -> Option<LatencyStats> {
+    let mut merged = MERGED.lock().unwrap();
+    EPOCH.fetch_add(1, Ordering::AcqRel);
+    merged
+        .take()
+        .filter(|histogram| !histogram.is_empty())
+        .map(|histogram| LatencyStats::from_histogram(&histogram))
+}
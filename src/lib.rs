@@ -0,0 +1,22 @@
+pub mod affinity;
+pub mod alloc;
+pub mod compare;
+pub mod complexity;
+pub mod config;
+pub mod crossover;
+pub mod export;
+pub mod gate;
+pub mod glob;
+pub mod groups;
+pub mod hwinfo;
+pub mod impls;
+pub mod latency;
+pub mod params;
+pub mod paths;
+pub mod perf;
+pub mod probe;
+pub mod profile;
+pub mod report;
+pub mod results;
+pub mod sidecar;
+pub mod workload;
@@ -0,0 +1,122 @@
+//! Typed benchmark parameters.
+//!
+//! A group declares its dimensions once as a list of [`Dim`]s, and every
+//! `BenchmarkId` parameter is a [`Params`] holding one value per dimension.
+//! The text form keeps the compact encoding the groups always used, e.g.
+//! `4t10000c1` for tasks, increments and capacity, and parses back into named
+//! values. Trailing dimensions are optional, so `4t10000` is valid too.
+
+use std::{error::Error, fmt};
+
+/// One dimension of a group's parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dim {
+    /// Column name, e.g. `tasks`.
+    pub name: &'static str,
+    /// Text written before the value; empty for the first dimension.
+    pub prefix: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    dims: &'static [Dim],
+    values: Vec<usize>,
+}
+
+impl Params {
+    /// Builds parameters from the leading `values` of `dims`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more values than dimensions.
+    pub fn new(dims: &'static [Dim], values: &[usize]) -> Self {
+        assert!(
+            values.len() <= dims.len(),
+            "{} values for {} dimensions",
+            values.len(),
+            dims.len()
+        );
+        Self {
+            dims,
+            values: values.to_vec(),
+        }
+    }
+
+    /// Parses the text form of parameters declared by `dims`.
+    pub fn parse(dims: &'static [Dim], text: &str) -> Result<Self, ParseParamsError> {
+        let mut values = Vec::new();
+        let mut rest = text;
+        for dim in dims {
+            if rest.is_empty() && !values.is_empty() {
+                break;
+            }
+            rest = rest
+                .strip_prefix(dim.prefix)
+                .ok_or_else(|| ParseParamsError::new(text, dim))?;
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let value = rest[..digits]
+                .parse()
+                .map_err(|_| ParseParamsError::new(text, dim))?;
+            values.push(value);
+            rest = &rest[digits..];
+        }
+        if !rest.is_empty() {
+            return Err(ParseParamsError {
+                text: text.to_string(),
+                reason: format!("unexpected trailing `{rest}`"),
+            });
+        }
+        Ok(Self { dims, values })
+    }
+
+    /// Value of the dimension called `name`, if present.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.iter().find(|(dim, _)| *dim == name).map(|(_, v)| v)
+    }
+
+    /// `(name, value)` of every present dimension, in declaration order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        self.dims
+            .iter()
+            .zip(&self.values)
+            .map(|(dim, &value)| (dim.name, value))
+    }
+
+    pub fn dims(&self) -> &'static [Dim] {
+        self.dims
+    }
+}
+
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (dim, value) in self.dims.iter().zip(&self.values) {
+            write!(f, "{}{value}", dim.prefix)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseParamsError {
+    text: String,
+    reason: String,
+}
+
+impl ParseParamsError {
+    fn new(text: &str, dim: &Dim) -> Self {
+        Self {
+            text: text.to_string(),
+            reason: format!("expected `{}<{}>`", dim.prefix, dim.name),
+        }
+    }
+}
+
+impl fmt::Display for ParseParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid parameters `{}`: {}", self.text, self.reason)
+    }
+}
+
+impl Error for ParseParamsError {}
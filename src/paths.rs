@@ -0,0 +1,21 @@
+//! Where bench targets and tools read and write their output.
+
+use std::{env, path::PathBuf};
+
+/// Criterion's output directory, resolved the same way Criterion does:
+/// `$CRITERION_HOME`, then `$CARGO_TARGET_DIR/criterion`, then
+/// `target/criterion`.
+pub fn criterion_home() -> PathBuf {
+    if let Some(home) = env::var_os("CRITERION_HOME") {
+        PathBuf::from(home)
+    } else {
+        target_dir().join("criterion")
+    }
+}
+
+/// Cargo's target directory: `$CARGO_TARGET_DIR`, then `target`.
+pub fn target_dir() -> PathBuf {
+    env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("target"))
+}
@@ -0,0 +1,299 @@
+//! Performance counters read through Linux `perf_event_open`.
+//!
+//! Groups that opt in get their instructions, cache, LLC, dTLB and branch
+//! misses per iteration recorded next to their timings. Where the PMU is not
+//! exposed, as in most VMs and containers, the counters fall back to software
+//! events (task clock, page faults, context switches, CPU migrations), and
+//! [`PerfStats`] records which source and counters were active. Other systems
+//! record nothing.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Sidecar kind holding the [`PerfStats`] of every benchmark.
+pub const SIDECAR: &str = "perf";
+
+/// Whether performance counters can be read on this system at all.
+pub const SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// An event to open, as `perf_event_attr`'s `type` and `config`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    pub name: &'static str,
+    kind: u32,
+    config: u64,
+}
+
+const TYPE_HARDWARE: u32 = 0;
+const TYPE_SOFTWARE: u32 = 1;
+const TYPE_HW_CACHE: u32 = 3;
+
+/// `PERF_TYPE_HW_CACHE` config for read misses of `cache`.
+const fn read_misses(cache: u64) -> u64 {
+    const OP_READ: u64 = 0;
+    const RESULT_MISS: u64 = 1;
+    cache | (OP_READ << 8) | (RESULT_MISS << 16)
+}
+
+pub const HARDWARE: &[Event] = &[
+    Event {
+        name: "instructions",
+        kind: TYPE_HARDWARE,
+        config: 1,
+    },
+    Event {
+        name: "cache_misses",
+        kind: TYPE_HARDWARE,
+        config: 3,
+    },
+    Event {
+        name: "llc_misses",
+        kind: TYPE_HW_CACHE,
+        config: read_misses(2),
+    },
+    Event {
+        name: "dtlb_misses",
+        kind: TYPE_HW_CACHE,
+        config: read_misses(3),
+    },
+    Event {
+        name: "branch_misses",
+        kind: TYPE_HARDWARE,
+        config: 5,
+    },
+];
+
+pub const SOFTWARE: &[Event] = &[
+    Event {
+        name: "task_clock_ns",
+        kind: TYPE_SOFTWARE,
+        config: 1,
+    },
+    Event {
+        name: "page_faults",
+        kind: TYPE_SOFTWARE,
+        config: 2,
+    },
+    Event {
+        name: "context_switches",
+        kind: TYPE_SOFTWARE,
+        config: 3,
+    },
+    Event {
+        name: "cpu_migrations",
+        kind: TYPE_SOFTWARE,
+        config: 4,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Hardware,
+    Software,
+}
+
+/// Counter values, either cumulative or per iteration. Only the counters that
+/// could be opened are present.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerfStats {
+    pub source: Source,
+    pub counters: BTreeMap<String, f64>,
+}
+
+impl PerfStats {
+    /// Averages the counters over `iterations`.
+    pub fn per(mut self, iterations: u64) -> Self {
+        let n = iterations.max(1) as f64;
+        self.counters.values_mut().for_each(|value| *value /= n);
+        self
+    }
+}
+
+/// Counters of the calling thread and the threads it spawns, opened disabled.
+pub struct Counters {
+    source: Source,
+    open: Vec<(&'static Event, sys::Fd)>,
+}
+
+impl Counters {
+    /// Opens every hardware event available, or the software events when no
+    /// hardware event is.
+    pub fn open() -> Self {
+        let open_all = |events: &'static [Event]| {
+            events
+                .iter()
+                .filter_map(|event| Some((event, sys::open(event)?)))
+                .collect::<Vec<_>>()
+        };
+        let open = open_all(HARDWARE);
+        if !open.is_empty() {
+            return Self {
+                source: Source::Hardware,
+                open,
+            };
+        }
+        Self {
+            source: Source::Software,
+            open: open_all(SOFTWARE),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    /// Resets and enables every counter.
+    pub fn start(&self) {
+        self.open.iter().for_each(|(_, fd)| sys::start(fd));
+    }
+
+    /// Disables every counter, keeping its count.
+    pub fn pause(&self) {
+        self.open.iter().for_each(|(_, fd)| sys::stop(fd));
+    }
+
+    /// Enables every counter again after [`Counters::pause`], adding to its
+    /// count.
+    pub fn resume(&self) {
+        self.open.iter().for_each(|(_, fd)| sys::resume(fd));
+    }
+
+    /// Disables every counter and reads it, scaled up for the time it was
+    /// multiplexed out.
+    pub fn stop(&self) -> PerfStats {
+        self.open.iter().for_each(|(_, fd)| sys::stop(fd));
+        PerfStats {
+            source: self.source,
+            counters: self
+                .open
+                .iter()
+                .filter_map(|(event, fd)| Some((event.name.to_string(), sys::read(fd)?)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::Event;
+    use std::{
+        fs::File,
+        io::Read,
+        os::fd::{AsRawFd, FromRawFd},
+    };
+
+    /// `struct perf_event_attr` up to `PERF_ATTR_SIZE_VER5`.
+    #[repr(C)]
+    #[derive(Default)]
+    struct Attr {
+        kind: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+        config2: u64,
+        branch_sample_type: u64,
+        sample_regs_user: u64,
+        sample_stack_user: u32,
+        clockid: i32,
+        sample_regs_intr: u64,
+        aux_watermark: u32,
+        sample_max_stack: u16,
+        reserved: u16,
+    }
+
+    const FLAG_DISABLED: u64 = 1 << 0;
+    const FLAG_INHERIT: u64 = 1 << 1;
+    const FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const FLAG_EXCLUDE_HV: u64 = 1 << 6;
+    const FORMAT_TOTAL_TIME_ENABLED: u64 = 1 << 0;
+    const FORMAT_TOTAL_TIME_RUNNING: u64 = 1 << 1;
+    const FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+    const IOC_ENABLE: libc::c_ulong = 0x2400;
+    const IOC_DISABLE: libc::c_ulong = 0x2401;
+    const IOC_RESET: libc::c_ulong = 0x2403;
+
+    pub struct Fd(File);
+
+    pub fn open(event: &Event) -> Option<Fd> {
+        let attr = Attr {
+            kind: event.kind,
+            size: size_of::<Attr>() as u32,
+            config: event.config,
+            read_format: FORMAT_TOTAL_TIME_ENABLED | FORMAT_TOTAL_TIME_RUNNING,
+            flags: FLAG_DISABLED | FLAG_INHERIT | FLAG_EXCLUDE_KERNEL | FLAG_EXCLUDE_HV,
+            ..Attr::default()
+        };
+        // this process on any CPU, without a group leader
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const Attr,
+                0,
+                -1,
+                -1,
+                FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return None;
+        }
+        Some(Fd(unsafe { File::from_raw_fd(fd as i32) }))
+    }
+
+    fn ioctl(fd: &Fd, request: libc::c_ulong) {
+        unsafe { libc::ioctl(fd.0.as_raw_fd(), request as _, 0) };
+    }
+
+    pub fn start(fd: &Fd) {
+        ioctl(fd, IOC_RESET);
+        ioctl(fd, IOC_ENABLE);
+    }
+
+    pub fn resume(fd: &Fd) {
+        ioctl(fd, IOC_ENABLE);
+    }
+
+    pub fn stop(fd: &Fd) {
+        ioctl(fd, IOC_DISABLE);
+    }
+
+    pub fn read(fd: &Fd) -> Option<f64> {
+        let mut buf = [0u8; 24];
+        (&fd.0).read_exact(&mut buf).ok()?;
+        let word = |i: usize| u64::from_ne_bytes(buf[i * 8..i * 8 + 8].try_into().unwrap());
+        let (value, enabled, running) = (word(0), word(1), word(2));
+        if running == 0 {
+            return None;
+        }
+        Some(value as f64 * enabled as f64 / running as f64)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use super::Event;
+
+    pub struct Fd;
+
+    pub fn open(_: &Event) -> Option<Fd> {
+        None
+    }
+
+    pub fn start(_: &Fd) {}
+
+    pub fn resume(_: &Fd) {}
+
+    pub fn stop(_: &Fd) {}
+
+    pub fn read(_: &Fd) -> Option<f64> {
+        None
+    }
+}
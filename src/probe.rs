@@ -0,0 +1,223 @@
+//! Side measurements taken once per benchmark, outside Criterion's timing
+//! loop, and stored as [`sidecar`]s next to its estimates: allocation counts
+//! in `alloc-count` builds, and performance counters and per-operation
+//! latencies for groups that opt in. Latencies get a pass of their own, which
+//! runs the routine with its operations timed, so neither the other passes
+//! nor Criterion pay for the recorders.
+//!
+//! Criterion only calls the closure of a benchmark its filter selects, but
+//! calls it again for every sample, so each benchmark is probed the first
+//! time its closure runs. Its measurements reach the sidecars when the group
+//! is closed with [`finish`].
+
+use crate::{
+    alloc::{self, AllocStats},
+    groups::{self, BenchId},
+    latency,
+    perf::{self, Counters},
+    sidecar,
+};
+use criterion::{BatchSize, Bencher, BenchmarkGroup, black_box, measurement::Measurement};
+use std::{
+    collections::BTreeSet,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Time the probe spends on each benchmark, after one warm-up iteration.
+const PROBE_TIME: Duration = Duration::from_millis(50);
+const MAX_ITERATIONS: u128 = 1000;
+/// Inputs prepared at a time, so the probe holds only a few of them however
+/// large each is.
+const BATCH: u64 = 32;
+
+static PROBED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Probes `routine`, then times it with [`Bencher::iter`].
+pub fn iter<O, R>(b: &mut Bencher, id: &BenchId, mut routine: R)
+where
+    R: FnMut() -> O,
+{
+    probe(
+        id,
+        || (),
+        |()| {
+            black_box(routine());
+        },
+        None,
+    );
+    b.iter(routine);
+}
+
+/// Probes `routine` on inputs from `setup`, then times it with
+/// [`Bencher::iter_batched`]. Neither the probe nor Criterion count `setup`,
+/// or dropping what `routine` returns.
+pub fn iter_batched<I, O, S, R>(
+    b: &mut Bencher,
+    id: &BenchId,
+    mut setup: S,
+    mut routine: R,
+    size: BatchSize,
+) where
+    S: FnMut() -> I,
+    R: FnMut(I) -> O,
+{
+    probe(id, &mut setup, &mut routine, None);
+    b.iter_batched(setup, routine, size);
+}
+
+/// Probes `routine`, then times it on `rt` like `b.to_async(rt).iter(..)`.
+pub fn iter_async<O, F, R>(b: &mut Bencher, rt: &Runtime, id: &BenchId, mut routine: R)
+where
+    R: FnMut() -> F,
+    F: Future<Output = O>,
+{
+    probe(
+        id,
+        || (),
+        |()| {
+            black_box(rt.block_on(routine()));
+        },
+        None,
+    );
+    b.to_async(rt).iter(routine);
+}
+
+/// Like [`iter_async`], but the latency pass runs `recorded`: the same
+/// routine timing its operations with a [`latency::Recorder`], where
+/// `routine` takes a [`latency::NoRecorder`].
+pub fn iter_async_recorded<O, F, R, G, Q>(
+    b: &mut Bencher,
+    rt: &Runtime,
+    id: &BenchId,
+    mut routine: R,
+    mut recorded: Q,
+) where
+    R: FnMut() -> F,
+    F: Future<Output = O>,
+    Q: FnMut() -> G,
+    G: Future,
+{
+    probe(
+        id,
+        || (),
+        |()| {
+            black_box(rt.block_on(routine()));
+        },
+        Some(&mut |()| {
+            black_box(rt.block_on(recorded()));
+        }),
+    );
+    b.to_async(rt).iter(routine);
+}
+
+/// Finishes `group` and stores the side measurements of its benchmarks.
+pub fn finish<M: Measurement>(group: BenchmarkGroup<'_, M>) {
+    group.finish();
+    sidecar::flush().expect("failed to store side measurements");
+}
+
+/// Runs the passes of the side measurements of `bench`, each on fresh inputs
+/// from `setup`. The latency pass runs `recorded` when given, else `run`.
+fn probe<I, O>(
+    bench: &BenchId,
+    mut setup: impl FnMut() -> I,
+    mut run: impl FnMut(I) -> O,
+    mut recorded: Option<&mut dyn FnMut(I)>,
+) {
+    let id = bench.to_string();
+    if !PROBED.lock().unwrap().insert(id.clone()) {
+        return;
+    }
+    let group = groups::find(bench.group);
+    let counters = group
+        .is_some_and(|group| group.counters)
+        .then(Counters::open)
+        .filter(|counters| !counters.is_empty());
+    let latencies = group.is_some_and(|group| group.latency);
+
+    // stale measurements would be reported with this run's timings
+    if !alloc::ENABLED {
+        sidecar::remove(alloc::SIDECAR, &id);
+    }
+    if counters.is_none() {
+        sidecar::remove(perf::SIDECAR, &id);
+    }
+    if !latencies {
+        sidecar::remove(latency::SIDECAR, &id);
+    }
+    if !alloc::ENABLED && counters.is_none() && !latencies {
+        return;
+    }
+
+    // the warm-up iteration also absorbs lazy initialisation
+    let start = Instant::now();
+    drop(run(setup()));
+    let once = start.elapsed().as_nanos().max(1);
+    let iterations = (PROBE_TIME.as_nanos() / once).clamp(1, MAX_ITERATIONS) as u64;
+    if alloc::ENABLED || counters.is_some() {
+        let mut allocs = AllocStats::default();
+        for (i, batch) in batches(iterations).enumerate() {
+            let (mut inputs, mut outputs) = prepare(batch, &mut setup);
+            let before = AllocStats::now();
+            if let Some(counters) = &counters {
+                match i {
+                    0 => counters.start(),
+                    _ => counters.resume(),
+                }
+            }
+            outputs.extend(inputs.drain(..).map(&mut run));
+            if let Some(counters) = &counters {
+                counters.pause();
+            }
+            allocs = allocs + (AllocStats::now() - before);
+            drop(outputs);
+        }
+        let perf = counters.map(|counters| counters.stop().per(iterations));
+        let allocs = allocs.per(iterations);
+
+        if alloc::ENABLED {
+            sidecar::write(alloc::SIDECAR, &id, &allocs)
+                .expect("failed to record allocation counts");
+        }
+        if let Some(perf) = perf {
+            sidecar::write(perf::SIDECAR, &id, &perf)
+                .expect("failed to record performance counters");
+        }
+    }
+
+    if latencies {
+        latency::begin();
+        for batch in batches(iterations) {
+            let (mut inputs, mut outputs) = prepare(batch, &mut setup);
+            match recorded.as_mut() {
+                Some(recorded) => inputs.drain(..).for_each(recorded),
+                None => outputs.extend(inputs.drain(..).map(&mut run)),
+            }
+        }
+        match latency::finish() {
+            Some(stats) => {
+                sidecar::write(latency::SIDECAR, &id, &stats).expect("failed to record latencies")
+            }
+            None => sidecar::remove(latency::SIDECAR, &id),
+        }
+    }
+}
+
+/// Sizes of the batches `iterations` are run in, at most [`BATCH`] each.
+fn batches(iterations: u64) -> impl Iterator<Item = u64> {
+    (0..iterations)
+        .step_by(BATCH as usize)
+        .map(move |start| (iterations - start).min(BATCH))
+}
+
+/// One input per iteration of a batch, and room for every output, so neither
+/// setting up nor storing allocates while the routine is counted. Outputs are
+/// dropped after counting, as [`Bencher::iter_batched`] drops them outside
+/// its timing.
+fn prepare<I, O>(iterations: u64, mut setup: impl FnMut() -> I) -> (Vec<I>, Vec<O>) {
+    let inputs = (0..iterations).map(|_| setup()).collect::<Vec<_>>();
+    (inputs, Vec::with_capacity(iterations as usize))
+}
@@ -0,0 +1,95 @@
+//! Quick and full measurement profiles, selected once for every group.
+//!
+//! The full profile keeps Criterion's defaults and every swept value, for
+//! numbers worth publishing. The quick profile takes few short samples and
+//! thins each default sweep to a quarter of its values, but at least its
+//! smallest and largest, evenly spaced between them. That gets the order of
+//! magnitude of a group in a few minutes at most, and of every group in their
+//! sum. Values configured in `napkin.toml` or `NAPKIN_SET` are never thinned.
+
+use criterion::Criterion;
+use std::{env, fmt, str::FromStr, sync::OnceLock, time::Duration};
+
+/// Profile name, `full` when unset.
+pub const PROFILE_VAR: &str = "NAPKIN_PROFILE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Quick,
+    Full,
+}
+
+impl Profile {
+    /// The profile of this process, from [`PROFILE_VAR`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variable names no profile.
+    pub fn current() -> Self {
+        static CURRENT: OnceLock<Profile> = OnceLock::new();
+        *CURRENT.get_or_init(|| match env::var(PROFILE_VAR) {
+            Ok(name) => name.parse().unwrap_or_else(|e| panic!("{e}")),
+            Err(_) => Profile::Full,
+        })
+    }
+
+    /// Criterion configured for this profile. Arguments given after `--`
+    /// still override it.
+    pub fn criterion(&self) -> Criterion {
+        match self {
+            Profile::Quick => Criterion::default()
+                .warm_up_time(Duration::from_millis(50))
+                .measurement_time(Duration::from_millis(200))
+                .sample_size(10)
+                .nresamples(1_000)
+                .without_plots(),
+            Profile::Full => Criterion::default(),
+        }
+    }
+
+    /// The values of a default sweep this profile measures.
+    pub fn thin(&self, values: &[usize]) -> Vec<usize> {
+        match self {
+            Profile::Quick => {
+                let mut sorted = values.to_vec();
+                sorted.sort_unstable();
+                // a quarter of the values, evenly spaced from the smallest to
+                // the largest
+                let last = sorted.len().saturating_sub(1);
+                let keep = sorted.len().div_ceil(4).max(2).min(sorted.len());
+                if keep < 2 {
+                    return sorted;
+                }
+                (0..keep).map(|i| sorted[i * last / (keep - 1)]).collect()
+            }
+            Profile::Full => values.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Profile::Quick => "quick",
+            Profile::Full => "full",
+        })
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        match text {
+            "quick" => Ok(Profile::Quick),
+            "full" => Ok(Profile::Full),
+            _ => Err(format!("unknown profile `{text}`, expected quick or full")),
+        }
+    }
+}
+
+/// Criterion configured for the current profile; every bench target passes
+/// this to `criterion_group!`.
+pub fn criterion() -> Criterion {
+    Profile::current().criterion()
+}
@@ -0,0 +1,318 @@
+//! Markdown comparison tables.
+//!
+//! One table per group and parameter point, listing every implementation
+//! measured there from fastest to slowest with its mean time, cost per
+//! operation, bandwidth when meaningful, and its ratio to the fastest one.
+//! Points with recorded latencies also list each implementation's p50, p99 and
+//! p99.9 per operation. Implementations sweeping dimensions of their own are
+//! ranked on the dimensions they share with the rest of their group. A final
+//! table lists the complexity model each implementation fits along its group's
+//! size dimensions, flagging fits that differ from the expected one.
+
+use crate::{
+    complexity::{self, Fit, Verdict},
+    export::Row,
+    groups,
+    hwinfo::HardwareProfile,
+};
+use std::fmt::Write;
+
+/// Marks the start of the generated section of `Readme.md`.
+pub const BEGIN_MARKER: &str = "<!-- napkin:results:begin -->";
+/// Marks the end of the generated section of `Readme.md`.
+pub const END_MARKER: &str = "<!-- napkin:results:end -->";
+
+/// Rows measured at one parameter point of one group.
+pub struct Point<'a> {
+    pub group: &'a str,
+    /// The dimensions every row of the group shares, see [`shared_dims`].
+    pub parameter: String,
+    /// Leading dimensions of the rows' parameters the point is made of.
+    pub shared: usize,
+    /// Sorted by mean time, fastest first.
+    pub rows: Vec<&'a Row>,
+}
+
+impl Point<'_> {
+    pub fn fastest(&self) -> &Row {
+        self.rows[0]
+    }
+
+    pub fn slowest(&self) -> &Row {
+        self.rows[self.rows.len() - 1]
+    }
+
+    /// Mean time of `row` relative to the fastest implementation.
+    pub fn ratio(&self, row: &Row) -> f64 {
+        row.mean.point / self.fastest().mean.point
+    }
+
+    /// `(name, value)` of the dimensions the point is made of.
+    pub fn dims(&self) -> &[(&'static str, usize)] {
+        &self.fastest().params[..self.shared]
+    }
+
+    /// The implementation of `row`, with the dimensions only it sweeps.
+    pub fn label(&self, row: &Row) -> String {
+        label(row, self.shared)
+    }
+}
+
+/// Number of leading dimensions every row of each group has. Implementations
+/// may sweep trailing dimensions of their own, like `mpsc_ch` and its channel
+/// `capacity` in the tokio groups, and are ranked against the others on the
+/// dimensions they share.
+pub fn shared_dims<'a>(rows: impl IntoIterator<Item = &'a Row>) -> Vec<(&'a str, usize)> {
+    let mut shared: Vec<(&str, usize)> = Vec::new();
+    for row in rows {
+        match shared.iter_mut().find(|(group, _)| *group == row.group) {
+            Some((_, dims)) => *dims = (*dims).min(row.params.len()),
+            None => shared.push((&row.group, row.params.len())),
+        }
+    }
+    shared
+}
+
+/// The parameter `row` is ranked at: its leading `shared` dimensions, or its
+/// raw parameter when its group is unknown.
+pub fn shared_parameter(row: &Row, shared: usize) -> String {
+    match groups::find(&row.group) {
+        Some(group) if !row.params.is_empty() => {
+            let values = row.params[..shared].iter().map(|(_, v)| *v);
+            group.params(&values.collect::<Vec<_>>()).to_string()
+        }
+        _ => row.parameter.clone(),
+    }
+}
+
+/// The implementation of `row`, followed by its dimensions past the leading
+/// `shared` ones, e.g. `mpsc_ch (capacity=1)`.
+pub fn label(row: &Row, shared: usize) -> String {
+    match row.params.get(shared..).unwrap_or_default() {
+        [] => row.implementation.clone(),
+        extra => format!("{} ({})", row.implementation, format_dims(extra)),
+    }
+}
+
+/// Splits rows into parameter points, ordered by group (in registry order)
+/// then by parameter dimensions.
+pub fn points(rows: &[Row]) -> Vec<Point<'_>> {
+    let shared = shared_dims(rows);
+    let mut points: Vec<Point> = Vec::new();
+    for row in rows {
+        let dims = shared
+            .iter()
+            .find(|(group, _)| *group == row.group)
+            .map_or(0, |(_, dims)| *dims);
+        let parameter = shared_parameter(row, dims);
+        match points
+            .iter_mut()
+            .find(|p| p.group == row.group && p.parameter == parameter)
+        {
+            Some(point) => point.rows.push(row),
+            None => points.push(Point {
+                group: &row.group,
+                parameter,
+                shared: dims,
+                rows: vec![row],
+            }),
+        }
+    }
+
+    let group_rank = |name: &str| {
+        groups::GROUPS
+            .iter()
+            .position(|g| g.name == name)
+            .unwrap_or(usize::MAX)
+    };
+    let param_key = |point: &Point| point.dims().iter().map(|(_, v)| *v).collect::<Vec<_>>();
+    points.sort_by(|a, b| {
+        (group_rank(a.group), a.group, param_key(a), &a.parameter).cmp(&(
+            group_rank(b.group),
+            b.group,
+            param_key(b),
+            &b.parameter,
+        ))
+    });
+    for point in &mut points {
+        point
+            .rows
+            .sort_by(|a, b| a.mean.point.total_cmp(&b.mean.point));
+    }
+    points
+}
+
+/// Renders every parameter point as a Markdown table, preceded by the machine
+/// the results come from when known.
+pub fn markdown(rows: &[Row], hardware: Option<&HardwareProfile>) -> String {
+    let mut out = String::new();
+    if let Some(hw) = hardware {
+        let model = hw.model_name.as_deref().unwrap_or("unknown CPU");
+        let _ = writeln!(
+            out,
+            "Measured on {model} ({} logical CPUs, {}/{}).\n",
+            hw.logical_cpus, hw.os, hw.arch
+        );
+    }
+
+    let mut current_group = None;
+    for point in points(rows) {
+        if current_group != Some(point.group) {
+            let _ = writeln!(out, "### {}\n", point.group);
+            current_group = Some(point.group);
+        }
+
+        let _ = writeln!(
+            out,
+            "#### `{}` ({})\n",
+            point.parameter,
+            format_dims(point.dims())
+        );
+
+        if point.rows.len() > 1 {
+            let _ = writeln!(
+                out,
+                "{} is {:.1}x {} at {}.\n",
+                point.label(point.slowest()),
+                point.ratio(point.slowest()),
+                point.label(point.fastest()),
+                point.parameter,
+            );
+        }
+
+        let latencies = point.rows.iter().any(|row| row.latency.is_some());
+        if latencies {
+            let _ = writeln!(
+                out,
+                "| Implementation | Mean | ns/op | GB/s | vs fastest | p50 | p99 | p99.9 |"
+            );
+            let _ = writeln!(out, "|---|---:|---:|---:|---:|---:|---:|---:|");
+        } else {
+            let _ = writeln!(out, "| Implementation | Mean | ns/op | GB/s | vs fastest |");
+            let _ = writeln!(out, "|---|---:|---:|---:|---:|");
+        }
+        for row in &point.rows {
+            let _ = write!(
+                out,
+                "| {} | {} | {} | {} | {:.2}x |",
+                point.label(row),
+                format_ns(row.mean.point),
+                row.ns_per_op().map_or("-".into(), |ns| format!("{ns:.2}")),
+                row.gb_per_s().map_or("-".into(), |gbs| format!("{gbs:.2}")),
+                point.ratio(row)
+            );
+            if latencies {
+                let percentile = |ns: Option<f64>| ns.map_or("-".into(), format_ns);
+                let _ = write!(
+                    out,
+                    " {} | {} | {} |",
+                    percentile(row.latency.map(|l| l.p50_ns)),
+                    percentile(row.latency.map(|l| l.p99_ns)),
+                    percentile(row.latency.map(|l| l.p999_ns)),
+                );
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
+    let fits = complexity::fits(rows);
+    if !fits.is_empty() {
+        let _ = writeln!(out, "### Complexity\n");
+        let _ = writeln!(
+            out,
+            "| Group | Implementation | Along | Fixed | Fit | t(n) | R² | Expected | Check |"
+        );
+        let _ = writeln!(out, "|---|---|---|---|---|---|---:|---|---|");
+        for fit in &fits {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+                fit.group,
+                fit.implementation,
+                fit.dim,
+                format_fixed(fit),
+                fit.curve.model,
+                format_curve(fit),
+                fit.curve
+                    .r_squared
+                    .map_or("-".into(), |r| format!("{r:.3}")),
+                fit.expected.map_or("-".into(), |m| m.to_string()),
+                match (fit.expected, fit.verdict()) {
+                    (None, _) => String::new(),
+                    (Some(_), None) => "ok".into(),
+                    (Some(_), Some(verdict @ Verdict::NoGrowth)) => format!("**{verdict}**"),
+                    (Some(_), Some(verdict)) => verdict.to_string(),
+                },
+            );
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn format_fixed(fit: &Fit) -> String {
+    match fit.fixed.is_empty() {
+        true => "-".into(),
+        false => format_dims(&fit.fixed),
+    }
+}
+
+/// `name=value` of every dimension, comma-separated, naming the variants of
+/// dimensions that enumerate them.
+fn format_dims(dims: &[(&str, usize)]) -> String {
+    dims.iter()
+        .map(|&(name, value)| {
+            let value = groups::value_name(name, value).unwrap_or_else(|| value.to_string());
+            format!("{name}={value}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `a + b·g(n)`, or `a` alone for constants.
+fn format_curve(fit: &Fit) -> String {
+    let curve = fit.curve;
+    if curve.slope == 0.0 {
+        return format_ns(curve.intercept);
+    }
+    // slopes of quadratic and n log n fits are often well under a nanosecond
+    let slope = match curve.slope {
+        s if s >= 1.0 => format_ns(s),
+        s if s >= 0.001 => format!("{s:.3} ns"),
+        s => format!("{s:.2e} ns"),
+    };
+    format!(
+        "{} + {slope}·{}",
+        format_ns(curve.intercept),
+        curve.model.term_name()
+    )
+}
+
+/// Replaces the text between [`BEGIN_MARKER`] and [`END_MARKER`] in `readme`
+/// with `section`, or appends a `## Results` section holding both markers
+/// when they are missing.
+pub fn replace_section(readme: &str, section: &str) -> String {
+    let generated = format!("{BEGIN_MARKER}\n{}\n{END_MARKER}", section.trim_end());
+    match (readme.find(BEGIN_MARKER), readme.find(END_MARKER)) {
+        (Some(begin), Some(end)) if begin < end => format!(
+            "{}{generated}{}",
+            &readme[..begin],
+            &readme[end + END_MARKER.len()..]
+        ),
+        _ => format!("{}\n\n## Results\n\n{generated}\n", readme.trim_end()),
+    }
+}
+
+/// Formats nanoseconds with a unit picked for readability, e.g. `3.21 µs`.
+pub fn format_ns(ns: f64) -> String {
+    if ns.abs() < 1e3 {
+        format!("{ns:.2} ns")
+    } else if ns.abs() < 1e6 {
+        format!("{:.2} µs", ns / 1e3)
+    } else if ns.abs() < 1e9 {
+        format!("{:.2} ms", ns / 1e6)
+    } else {
+        format!("{:.2} s", ns / 1e9)
+    }
+}
@@ -0,0 +1,310 @@
+//! Benchmark results read back from Criterion's output directory, and the
+//! consolidated result set the `napkin` runner saves for every run.
+
+use crate::{
+    alloc::{self, AllocStats},
+    groups::{self, Work},
+    hwinfo::HardwareProfile,
+    latency::{self, LatencyStats},
+    params::Params,
+    paths,
+    perf::{self, PerfStats},
+    sidecar,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Where Criterion stores the latest measurement of each benchmark. Saved
+/// baselines live next to it under their own name.
+pub const NEW: &str = "new";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    pub confidence_level: f64,
+    pub lower_bound: f64,
+    pub upper_bound: f64,
+}
+
+/// A Criterion estimate, in nanoseconds per iteration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Estimate {
+    pub confidence_interval: ConfidenceInterval,
+    pub point_estimate: f64,
+    pub standard_error: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    /// Full id, `group/function/value`.
+    pub id: String,
+    pub group: String,
+    pub function: Option<String>,
+    pub value: Option<String>,
+    pub mean: Estimate,
+    pub median: Estimate,
+    pub std_dev: Estimate,
+    /// Allocator activity per iteration, for builds with `alloc-count`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alloc: Option<AllocStats>,
+    /// Performance counters per iteration, for groups recording them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perf: Option<PerfStats>,
+    /// Per-operation latency percentiles, for groups recording them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencyStats>,
+}
+
+impl BenchResult {
+    /// The benchmark parameter decoded into the dimensions declared by its
+    /// group, or `None` for unknown groups and undecodable values.
+    pub fn params(&self) -> Option<Params> {
+        groups::find(&self.group)?
+            .parse(self.value.as_deref()?)
+            .ok()
+    }
+
+    /// Work one iteration does, as declared by the group.
+    pub fn work(&self) -> Option<Work> {
+        (groups::find(&self.group)?.work)(&self.params()?)
+    }
+
+    /// Mean nanoseconds per element (operation).
+    pub fn ns_per_op(&self) -> Option<f64> {
+        Some(self.mean.point_estimate / self.work()?.elements as f64)
+    }
+
+    /// Mean bandwidth in GB/s, for benchmarks declaring the bytes they touch.
+    pub fn gb_per_s(&self) -> Option<f64> {
+        // bytes per nanosecond is gigabytes per second
+        Some(self.work()?.bytes? as f64 / self.mean.point_estimate)
+    }
+}
+
+/// The parts of Criterion's `benchmark.json` we read.
+#[derive(Deserialize)]
+struct BenchmarkJson {
+    group_id: String,
+    function_id: Option<String>,
+    value_str: Option<String>,
+    full_id: String,
+}
+
+/// The parts of Criterion's `estimates.json` we read.
+#[derive(Deserialize)]
+struct EstimatesJson {
+    mean: Estimate,
+    median: Estimate,
+    std_dev: Estimate,
+}
+
+/// Reads every benchmark stored under `criterion_home` for `source`, either
+/// [`NEW`] or the name of a saved baseline. Results are sorted by id.
+pub fn collect(criterion_home: &Path, source: &str) -> io::Result<Vec<BenchResult>> {
+    let mut results = Vec::new();
+    for dir in find_dirs(criterion_home, source)? {
+        results.push(read(&dir)?);
+    }
+    if source == NEW {
+        attach_sidecars(criterion_home, &mut results);
+    }
+    results.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(results)
+}
+
+/// Like [`collect`] for [`NEW`], keeping only benchmarks measured at or after
+/// `since`.
+pub fn collect_since(criterion_home: &Path, since: SystemTime) -> io::Result<Vec<BenchResult>> {
+    let mut results = Vec::new();
+    for dir in find_dirs(criterion_home, NEW)? {
+        let modified = fs::metadata(dir.join("estimates.json"))?.modified()?;
+        if modified >= since {
+            results.push(read(&dir)?);
+        }
+    }
+    attach_sidecars(criterion_home, &mut results);
+    results.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(results)
+}
+
+/// Attaches the side measurements of the latest run of each benchmark.
+fn attach_sidecars(criterion_home: &Path, results: &mut [BenchResult]) {
+    for (id, stats) in sidecar::read_all::<AllocStats>(criterion_home, alloc::SIDECAR) {
+        if let Some(result) = results.iter_mut().find(|result| result.id == id) {
+            result.alloc = Some(stats);
+        }
+    }
+    for (id, stats) in sidecar::read_all::<PerfStats>(criterion_home, perf::SIDECAR) {
+        if let Some(result) = results.iter_mut().find(|result| result.id == id) {
+            result.perf = Some(stats);
+        }
+    }
+    for (id, stats) in sidecar::read_all::<LatencyStats>(criterion_home, latency::SIDECAR) {
+        if let Some(result) = results.iter_mut().find(|result| result.id == id) {
+            result.latency = Some(stats);
+        }
+    }
+}
+
+fn read(dir: &Path) -> io::Result<BenchResult> {
+    let benchmark: BenchmarkJson = read_json(&dir.join("benchmark.json"))?;
+    let estimates: EstimatesJson = read_json(&dir.join("estimates.json"))?;
+    Ok(BenchResult {
+        id: benchmark.full_id,
+        group: benchmark.group_id,
+        function: benchmark.function_id,
+        value: benchmark.value_str,
+        mean: estimates.mean,
+        median: estimates.median,
+        std_dev: estimates.std_dev,
+        alloc: None,
+        perf: None,
+        latency: None,
+    })
+}
+
+/// Finds every `<source>` directory holding both `benchmark.json` and
+/// `estimates.json`. Criterion's own `report` directories are skipped.
+fn find_dirs(root: &Path, source: &str) -> io::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if !path.is_dir() || path.file_name().is_some_and(|n| n == "report") {
+                continue;
+            }
+            if path.file_name().is_some_and(|n| n == source)
+                && path.join("benchmark.json").is_file()
+                && path.join("estimates.json").is_file()
+            {
+                found.push(path);
+            } else {
+                pending.push(path);
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<T> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+/// One invocation of the `napkin` runner: what was selected, the machine it
+/// ran on and every benchmark it measured.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    pub id: String,
+    /// Start time, in seconds since the Unix epoch.
+    pub started: u64,
+    pub patterns: Vec<String>,
+    /// Measurement profile, when the run asked for one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// CPU placement of the benchmark and worker threads, when pinned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<String>,
+    pub hardware: HardwareProfile,
+    pub results: Vec<BenchResult>,
+}
+
+impl Run {
+    /// Directory holding the saved runs, `target/napkin/runs`.
+    pub fn dir() -> PathBuf {
+        paths::target_dir().join("napkin").join("runs")
+    }
+
+    /// Saves the run as `<dir>/<fingerprint>/<id>/run.json`, keyed by the
+    /// fingerprint of the machine it ran on, and returns that path.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        let path = dir
+            .join(self.hardware.fingerprint())
+            .join(&self.id)
+            .join("run.json");
+        fs::create_dir_all(path.parent().unwrap())?;
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// Loads a run saved with [`Run::save`], given either its `run.json` or
+    /// the directory holding it.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        if path.is_dir() {
+            read_json(&path.join("run.json"))
+        } else {
+            read_json(path)
+        }
+    }
+
+    /// Every saved run under `dir`, as `(fingerprint, id)` sorted by
+    /// fingerprint then id.
+    pub fn list(dir: &Path) -> io::Result<Vec<(String, String)>> {
+        let mut runs = Vec::new();
+        for machine in subdirs(dir)? {
+            for run in subdirs(&dir.join(&machine))? {
+                if dir.join(&machine).join(&run).join("run.json").is_file() {
+                    runs.push((machine.clone(), run));
+                }
+            }
+        }
+        runs.sort();
+        Ok(runs)
+    }
+
+    /// Finds a saved run from `spec`: a path to a run, `<fingerprint>/<id>`,
+    /// or a bare fingerprint for the latest run of that machine.
+    pub fn resolve(dir: &Path, spec: &str) -> io::Result<PathBuf> {
+        let path = Path::new(spec);
+        if path.exists() {
+            return Ok(path.to_path_buf());
+        }
+        let path = dir.join(spec);
+        if path.join("run.json").is_file() {
+            return Ok(path);
+        }
+        Self::list(dir)?
+            .into_iter()
+            .filter(|(machine, _)| machine == spec)
+            .max_by_key(|(_, id)| started(id))
+            .map(|(machine, id)| dir.join(machine).join(id))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no run `{spec}`")))
+    }
+}
+
+/// Start time encoded in a run id, `<seconds>.<nanoseconds>` or
+/// `<seconds>.<nanoseconds>-<placement>`, as seconds and nanoseconds.
+fn started(id: &str) -> (u64, u32) {
+    id.split('-')
+        .next()
+        .and_then(|stamp| stamp.split_once('.'))
+        .and_then(|(secs, nanos)| Some((secs.parse().ok()?, nanos.parse().ok()?)))
+        .unwrap_or((0, 0))
+}
+
+fn subdirs(dir: &Path) -> io::Result<Vec<String>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(names)
+}
@@ -0,0 +1,89 @@
+//! Side measurements stored next to Criterion's output.
+//!
+//! Each kind of side measurement (allocations, ...) lives in
+//! `<criterion home>/napkin/<kind>.json`, a map from benchmark id to the
+//! measurement, so results collected from Criterion can pick them up.
+//! Benchmarks [`write()`] and [`remove`] measurements in memory, and each group
+//! applies them with one [`flush`] when it finishes.
+
+use crate::paths;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, fs, io, path::Path, sync::Mutex};
+
+/// Changes not yet flushed, by kind then benchmark id: the measurement to
+/// store, or `None` to forget it.
+type Changes = BTreeMap<String, BTreeMap<String, Option<Value>>>;
+
+static PENDING: Mutex<Changes> = Mutex::new(BTreeMap::new());
+
+fn path(criterion_home: &Path, kind: &str) -> std::path::PathBuf {
+    criterion_home.join("napkin").join(format!("{kind}.json"))
+}
+
+fn read_map(criterion_home: &Path, kind: &str) -> Map<String, Value> {
+    fs::read_to_string(path(criterion_home, kind))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Stores the `kind` measurement of benchmark `id` at the next [`flush`],
+/// replacing any previous one.
+pub fn write<T: Serialize>(kind: &str, id: &str, value: &T) -> io::Result<()> {
+    let value = serde_json::to_value(value).map_err(io::Error::other)?;
+    change(kind, id, Some(value));
+    Ok(())
+}
+
+/// Forgets the `kind` measurement of benchmark `id` at the next [`flush`], so
+/// a later run that did not take it is not reported with a stale one.
+pub fn remove(kind: &str, id: &str) {
+    change(kind, id, None);
+}
+
+fn change(kind: &str, id: &str, value: Option<Value>) {
+    PENDING
+        .lock()
+        .unwrap()
+        .entry(kind.to_string())
+        .or_default()
+        .insert(id.to_string(), value);
+}
+
+/// Applies every pending change, reading and rewriting each kind's file
+/// once.
+pub fn flush() -> io::Result<()> {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap());
+    let home = paths::criterion_home();
+    for (kind, changes) in pending {
+        let mut map = read_map(&home, &kind);
+        let mut changed = false;
+        for (id, value) in changes {
+            changed |= match value {
+                Some(value) => {
+                    map.insert(id, value);
+                    true
+                }
+                None => map.shift_remove(&id).is_some(),
+            };
+        }
+        if !changed {
+            continue;
+        }
+
+        let path = path(&home, &kind);
+        fs::create_dir_all(path.parent().unwrap())?;
+        let json = serde_json::to_string_pretty(&map).map_err(io::Error::other)?;
+        fs::write(path, json)?;
+    }
+    Ok(())
+}
+
+/// Reads every `kind` measurement stored under `criterion_home`.
+pub fn read_all<T: DeserializeOwned>(criterion_home: &Path, kind: &str) -> Vec<(String, T)> {
+    read_map(criterion_home, kind)
+        .into_iter()
+        .filter_map(|(id, value)| Some((id, serde_json::from_value(value).ok()?)))
+        .collect()
+}
@@ -0,0 +1,386 @@
+//! Seeded input generators shared by every bench target.
+//!
+//! Every group starts from [`rng`], so two groups asking for the same keys,
+//! payloads or access orders in the same sequence get the same inputs.
+
+use rand::{
+    Rng, SeedableRng,
+    rngs::StdRng,
+    seq::{SliceRandom, index},
+};
+use std::{collections::HashSet, fmt, iter, str::FromStr};
+
+/// Seed used by every bench target.
+pub const SEED: u64 = 64;
+
+/// Opaque blob stored under each key.
+pub type Payload = Vec<u8>;
+
+/// Returns a fresh generator seeded with [`SEED`].
+pub fn rng() -> StdRng {
+    StdRng::seed_from_u64(SEED)
+}
+
+/// Dense keys `0..len`.
+pub fn dense_keys(len: usize) -> Vec<usize> {
+    (0..len).collect()
+}
+
+/// `len` distinct keys drawn uniformly from `0..range`, in random order.
+/// Duplicates would make collections that keep one entry per key hold fewer
+/// entries than the others.
+///
+/// # Panics
+///
+/// Panics if `len` exceeds `range`.
+pub fn random_keys(rng: &mut impl Rng, len: usize, range: usize) -> Vec<usize> {
+    index::sample(rng, range, len).into_vec()
+}
+
+/// A payload of `size` bytes, all set to `byte`.
+pub fn payload(size: usize, byte: u8) -> Payload {
+    vec![byte; size]
+}
+
+/// Pairs every key with its own copy of `payload`, ready to be collected into
+/// any collection.
+pub fn entries<'a>(
+    keys: &'a [usize],
+    payload: &'a Payload,
+) -> impl Iterator<Item = (usize, Payload)> + 'a {
+    keys.iter().map(move |&k| (k, payload.clone()))
+}
+
+/// Returns a shuffled copy of `items`.
+pub fn shuffled<T: Clone>(rng: &mut impl Rng, items: &[T]) -> Vec<T> {
+    let mut items = items.to_vec();
+    items.shuffle(rng);
+    items
+}
+
+/// One independently shuffled copy of `keys` per task.
+pub fn indices_per_task(rng: &mut impl Rng, keys: &[usize], tasks: usize) -> Vec<Vec<usize>> {
+    (0..tasks).map(|_| shuffled(rng, keys)).collect()
+}
+
+/// A random permutation of `0..len`, used to visit a buffer out of order.
+pub fn access_order(rng: &mut impl Rng, len: usize) -> Vec<usize> {
+    shuffled(rng, &dense_keys(len))
+}
+
+/// Picks one of `keys` uniformly.
+pub fn pick(rng: &mut impl Rng, keys: &[usize]) -> usize {
+    keys[rng.random_range(0..keys.len())]
+}
+
+/// Where the key a lookup targets sits among the keys of a collection, in
+/// insertion order. Its value in benchmark parameters is its discriminant,
+/// which never changes once results carry it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPosition {
+    /// Not in the collection: a miss, which scans sequences to their end.
+    Absent = 0,
+    Front = 1,
+    Middle = 2,
+    Back = 3,
+}
+
+impl KeyPosition {
+    pub const ALL: [KeyPosition; 4] = [
+        KeyPosition::Absent,
+        KeyPosition::Front,
+        KeyPosition::Middle,
+        KeyPosition::Back,
+    ];
+
+    /// The position a parameter value stands for.
+    pub fn from_value(value: usize) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|variant| variant.value() == value)
+    }
+
+    pub fn value(self) -> usize {
+        self as usize
+    }
+
+    /// The key at this position of `keys`, `None` when absent or empty.
+    pub fn key(self, keys: &[usize]) -> Option<usize> {
+        let at = match self {
+            KeyPosition::Absent => return None,
+            KeyPosition::Front => 0,
+            KeyPosition::Middle => keys.len() / 2,
+            KeyPosition::Back => keys.len().checked_sub(1)?,
+        };
+        keys.get(at).copied()
+    }
+}
+
+impl fmt::Display for KeyPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeyPosition::Absent => "absent",
+            KeyPosition::Front => "front",
+            KeyPosition::Middle => "middle",
+            KeyPosition::Back => "back",
+        })
+    }
+}
+
+impl FromStr for KeyPosition {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|position| position.to_string() == text)
+            .ok_or_else(|| {
+                format!("unknown key position `{text}`, expected front, middle, back or absent")
+            })
+    }
+}
+
+/// How the keys of a collection are spread over `usize`. Its value in
+/// benchmark parameters is its discriminant, which never changes once results
+/// carry it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyDistribution {
+    /// `0..len`, the best case for ordered maps and multiplicative hashes.
+    Dense = 0,
+    /// Uniform over the lower half of `usize`, like random IDs.
+    Sparse = 1,
+    /// Runs of [`CLUSTER_LEN`] consecutive keys at random bases, like IDs
+    /// allocated in blocks.
+    Clustered = 2,
+    /// Multiples of 2^[`POWER_OF_TWO_SHIFT`], like aligned addresses, whose
+    /// low bits are all zero.
+    PowerOfTwo = 3,
+    /// Keys whose `FxHash` differ only in bits 32 to 56, so every one lands
+    /// in the same bucket of an `FxHashMap` with the same control byte and
+    /// each lookup probes them all (on 64-bit targets).
+    FxCollisions = 4,
+}
+
+/// Keys per run of [`KeyDistribution::Clustered`].
+pub const CLUSTER_LEN: usize = 64;
+/// Shift of the keys of [`KeyDistribution::PowerOfTwo`].
+pub const POWER_OF_TWO_SHIFT: u32 = 20;
+
+impl KeyDistribution {
+    pub const ALL: [KeyDistribution; 5] = [
+        KeyDistribution::Dense,
+        KeyDistribution::Sparse,
+        KeyDistribution::Clustered,
+        KeyDistribution::PowerOfTwo,
+        KeyDistribution::FxCollisions,
+    ];
+
+    /// The distribution a parameter value stands for.
+    pub fn from_value(value: usize) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|variant| variant.value() == value)
+    }
+
+    pub fn value(self) -> usize {
+        self as usize
+    }
+
+    /// `len` distinct keys, in the order to insert them: ascending for dense
+    /// and power-of-two keys and for each cluster, random otherwise.
+    pub fn keys(self, rng: &mut impl Rng, len: usize) -> Vec<usize> {
+        match self {
+            KeyDistribution::Dense => dense_keys(len),
+            KeyDistribution::Sparse => {
+                distinct(rng, len, |rng| rng.random_range(0..usize::MAX / 2))
+            }
+            KeyDistribution::Clustered => {
+                let runs = len.div_ceil(CLUSTER_LEN);
+                // bases are multiples of the run length, so runs never overlap
+                let bases = distinct(rng, runs, |rng| {
+                    rng.random_range(0..usize::MAX / 2 / CLUSTER_LEN) * CLUSTER_LEN
+                });
+                bases
+                    .into_iter()
+                    .flat_map(|base| base..base + CLUSTER_LEN)
+                    .take(len)
+                    .collect()
+            }
+            KeyDistribution::PowerOfTwo => (0..len).map(|i| i << POWER_OF_TWO_SHIFT).collect(),
+            KeyDistribution::FxCollisions => index::sample(rng, 1 << 25, len)
+                .into_iter()
+                .map(fx_collision)
+                .collect(),
+        }
+    }
+}
+
+/// `len` distinct values drawn with `draw`.
+fn distinct<R: Rng>(rng: &mut R, len: usize, mut draw: impl FnMut(&mut R) -> usize) -> Vec<usize> {
+    let mut seen = HashSet::with_capacity(len);
+    let mut keys = Vec::with_capacity(len);
+    while keys.len() < len {
+        let key = draw(rng);
+        if seen.insert(key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// The key whose 64-bit `FxHash` is `i << 32`, for `i` below 2^25: the
+/// hasher multiplies by `K` and rotates left by 26, so the key is the
+/// rotated-back hash times the inverse of `K`.
+fn fx_collision(i: usize) -> usize {
+    const K: u64 = 0xf1357aea2e62a9c5;
+    // Newton's iteration doubles the correct low bits of the inverse of an
+    // odd number, starting from 3
+    let mut inverse = K;
+    for _ in 0..5 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(K.wrapping_mul(inverse)));
+    }
+    ((i as u64) << 32).rotate_right(26).wrapping_mul(inverse) as usize
+}
+
+impl fmt::Display for KeyDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeyDistribution::Dense => "dense",
+            KeyDistribution::Sparse => "sparse",
+            KeyDistribution::Clustered => "clustered",
+            KeyDistribution::PowerOfTwo => "power-of-two",
+            KeyDistribution::FxCollisions => "fx-collisions",
+        })
+    }
+}
+
+impl FromStr for KeyDistribution {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|distribution| distribution.to_string() == text)
+            .ok_or_else(|| {
+                format!(
+                    "unknown key distribution `{text}`, expected dense, sparse, clustered, \
+                     power-of-two or fx-collisions"
+                )
+            })
+    }
+}
+
+/// `absent.len()` keys to look up in a collection holding `keys`, in random
+/// order. `hit_pct` percent of them (rounded) are the key at `position`, and
+/// the others the leading keys of `absent`, which the collection must not
+/// hold. Every lookup misses when `position` is [`KeyPosition::Absent`].
+pub fn lookups(
+    rng: &mut impl Rng,
+    keys: &[usize],
+    absent: &[usize],
+    hit_pct: usize,
+    position: KeyPosition,
+) -> Vec<usize> {
+    let count = absent.len();
+    let hit = position.key(keys);
+    let hits = hit.map_or(0, |_| (count * hit_pct + 50) / 100).min(count);
+    let lookups = iter::repeat_n(hit, hits)
+        .flatten()
+        .chain(absent[..count - hits].iter().copied())
+        .collect::<Vec<_>>();
+    shuffled(rng, &lookups)
+}
+
+/// How a stream of lookups picks its keys. Its value in benchmark parameters
+/// is its discriminant, which never changes once results carry it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupPattern {
+    /// Every key equally likely, independently.
+    Uniform = 0,
+    /// The keys in insertion order, wrapping around.
+    Sequential = 1,
+    /// Zipf-distributed ranks, so a few hot keys take most lookups. Ranks are
+    /// assigned to keys at random, so hot keys are not the first inserted.
+    Zipf = 2,
+}
+
+impl LookupPattern {
+    pub const ALL: [LookupPattern; 3] = [
+        LookupPattern::Uniform,
+        LookupPattern::Sequential,
+        LookupPattern::Zipf,
+    ];
+
+    /// The pattern a parameter value stands for.
+    pub fn from_value(value: usize) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|variant| variant.value() == value)
+    }
+
+    pub fn value(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for LookupPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LookupPattern::Uniform => "uniform",
+            LookupPattern::Sequential => "sequential",
+            LookupPattern::Zipf => "zipf",
+        })
+    }
+}
+
+impl FromStr for LookupPattern {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|pattern| pattern.to_string() == text)
+            .ok_or_else(|| {
+                format!("unknown lookup pattern `{text}`, expected uniform, sequential or zipf")
+            })
+    }
+}
+
+/// `count` keys of `keys` to look up, following `pattern`. `skew` is the
+/// Zipf exponent: the key of rank `r` (from 1) is drawn with a probability
+/// proportional to `1 / r^skew`, so 0 is uniform and 1 the classic Zipf law.
+/// Other patterns ignore it.
+///
+/// # Panics
+///
+/// Panics if `keys` is empty.
+pub fn lookup_stream(
+    rng: &mut impl Rng,
+    keys: &[usize],
+    count: usize,
+    pattern: LookupPattern,
+    skew: f64,
+) -> Vec<usize> {
+    assert!(!keys.is_empty(), "no keys to look up");
+    match pattern {
+        LookupPattern::Uniform => (0..count).map(|_| pick(rng, keys)).collect(),
+        LookupPattern::Sequential => keys.iter().copied().cycle().take(count).collect(),
+        LookupPattern::Zipf => {
+            let ranked = shuffled(rng, keys);
+            let mut total = 0.0;
+            let cdf = (1..=ranked.len())
+                .map(|rank| {
+                    total += (rank as f64).powf(-skew);
+                    total
+                })
+                .collect::<Vec<_>>();
+            (0..count)
+                .map(|_| {
+                    let u = rng.random_range(0.0..total);
+                    let rank = cdf.partition_point(|&c| c <= u).min(ranked.len() - 1);
+                    ranked[rank]
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use napkin_benches::{
+    affinity::{self, CpuSets, Placement},
+    hwinfo::HardwareProfile,
+};
+use std::path::Path;
+
+/// Two cores with two SMT siblings each, one L2 per core.
+fn fixture() -> HardwareProfile {
+    HardwareProfile::from_root(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hwinfo"))
+}
+
+fn sets(bench: &[usize], workers: &[usize]) -> Option<CpuSets> {
+    Some(CpuSets {
+        bench: bench.to_vec(),
+        workers: workers.to_vec(),
+    })
+}
+
+#[test]
+fn placements_follow_the_topology() {
+    let profile = fixture();
+    assert_eq!(Placement::SameCore.cpus(&profile), sets(&[0], &[0]));
+    assert_eq!(Placement::SmtSibling.cpus(&profile), sets(&[0], &[1]));
+    // each cluster is a single core
+    assert_eq!(Placement::SameCluster.cpus(&profile), None);
+    assert_eq!(Placement::OtherCluster.cpus(&profile), sets(&[0], &[2, 3]));
+
+    // four cores without SMT, two per cluster
+    let profile = HardwareProfile {
+        cores: vec![vec![0], vec![1], vec![2], vec![3]],
+        clusters: vec![vec![0, 1], vec![2, 3]],
+        ..Default::default()
+    };
+    assert_eq!(Placement::SmtSibling.cpus(&profile), None);
+    assert_eq!(Placement::SameCluster.cpus(&profile), sets(&[0], &[1]));
+    assert_eq!(Placement::OtherCluster.cpus(&profile), sets(&[0], &[2, 3]));
+
+    assert_eq!(Placement::SameCore.cpus(&HardwareProfile::default()), None);
+}
+
+#[test]
+fn cpu_lists_round_trip() {
+    let sets = Placement::OtherCluster.cpus(&fixture()).unwrap();
+    let [(_, bench), (_, workers)] = sets.env();
+    assert_eq!(affinity::parse_cpus(&bench), Ok(sets.bench));
+    assert_eq!(affinity::parse_cpus(&workers), Ok(sets.workers));
+    assert_eq!(
+        affinity::parse_cpus("0-3,8,10-11"),
+        Ok(vec![0, 1, 2, 3, 8, 10, 11])
+    );
+}
+
+#[test]
+fn malformed_cpu_lists_are_rejected() {
+    for list in ["", "0,", "a", "0-", "3-1", "0-3,x", "1 2", "-1"] {
+        let error = affinity::parse_cpus(list).expect_err(list);
+        assert!(
+            error.starts_with(&format!("invalid CPU list `{list}`")),
+            "{error}"
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn cpus_past_the_set_are_rejected() {
+    let error = affinity::pin_current_thread(&[0, 1 << 20]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,396 @@
+use napkin_benches::{
+    impls::collections::{self, BenchCollection, Entry, Visitor},
+    workload::{self, KeyDistribution, KeyPosition, LookupPattern},
+};
+use rustc_hash::FxBuildHasher;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    hash::BuildHasher,
+    str::FromStr,
+};
+
+/// Entries for `keys`, told apart by their payload lengths.
+fn entries(keys: &[usize]) -> Vec<Entry> {
+    keys.iter()
+        .enumerate()
+        .map(|(n, &key)| (key, workload::payload(n + 1, 1)))
+        .collect()
+}
+
+/// Keys not in `keys`.
+fn absent(keys: &[usize]) -> Vec<usize> {
+    (0..).filter(|key| !keys.contains(key)).take(3).collect()
+}
+
+/// `(key, payload length)` of every entry, in the collection's order.
+fn contents<C: BenchCollection>(collection: &C) -> Vec<(usize, usize)> {
+    let mut contents = Vec::new();
+    collection.for_each(|key, payload| contents.push((key, payload.len())));
+    contents
+}
+
+/// Looks up every key and a few absent ones, expecting the payload length
+/// each key was stored with from every collection.
+struct Find<'a>(&'a [usize]);
+
+impl Visitor for Find<'_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let keys = self.0;
+        let collection = C::build(entries(keys));
+        let lookups = keys
+            .iter()
+            .enumerate()
+            .map(|(n, &key)| (key, Some(n + 1)))
+            .chain(absent(keys).into_iter().map(|key| (key, None)));
+        for (key, expected) in lookups {
+            let name = C::NAME;
+            assert_eq!(
+                collection.find(key),
+                expected,
+                "{name} looking up {key} of {keys:?}"
+            );
+        }
+    }
+}
+
+/// Inserts the second half of `keys` into a collection built from the first,
+/// removes every other key, and compares what is left with a `BTreeMap`
+/// doing the same.
+struct Update<'a>(&'a [usize]);
+
+impl Visitor for Update<'_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let (name, keys) = (C::NAME, self.0);
+        let entries = entries(keys);
+        let (built, inserted) = entries.split_at(keys.len() / 2);
+        let mut collection = C::build(built.to_vec());
+        let mut expected = built.iter().cloned().collect::<BTreeMap<_, _>>();
+        for (key, payload) in inserted {
+            collection.insert(*key, payload.clone());
+            expected.insert(*key, payload.clone());
+        }
+        for key in keys.iter().step_by(2).chain(&absent(keys)) {
+            assert_eq!(
+                collection.remove(*key),
+                expected.remove(key),
+                "{name} removing {key} of {keys:?}"
+            );
+        }
+
+        let mut left = contents(&collection);
+        left.sort_unstable();
+        let expected = expected
+            .iter()
+            .map(|(key, payload)| (*key, payload.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(left, expected, "{name} after updates of {keys:?}");
+        for (key, len) in expected {
+            assert_eq!(collection.find(key), Some(len), "{name} looking up {key}");
+        }
+    }
+}
+
+/// Fills collections one key at a time, growing and presized, updates every
+/// other key in place and swap-removes every third, expecting the entries a
+/// `(key, payload length)` list doing the same holds, in its order where the
+/// collection swap-removes.
+struct Churn<'a>(&'a [usize]);
+
+impl Visitor for Churn<'_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let (name, keys) = (C::NAME, self.0);
+        let entries = entries(keys);
+        // hash maps iterate in an order of their own, so compare them sorted
+        let in_order = |mut contents: Vec<(usize, usize)>| {
+            if !C::SWAP_REMOVE {
+                contents.sort_unstable();
+            }
+            contents
+        };
+        let built = in_order(contents(&C::build(entries.clone())));
+        for presized in [false, true] {
+            let mut collection = collections::filled::<C>(&entries, presized);
+            let filled = in_order(contents(&collection));
+            assert_eq!(filled, built, "{name} filled, presized {presized}");
+
+            let mut expected = built.clone();
+            for (key, len) in expected.iter_mut().step_by(2) {
+                assert!(collection.update(*key, |payload| payload.push(0)));
+                *len += 1;
+            }
+            for key in absent(keys) {
+                assert!(!collection.update(key, |_| panic!("{name} updated absent {key}")));
+            }
+            for key in keys.iter().step_by(3) {
+                let at = expected.iter().position(|(k, _)| k == key).unwrap();
+                let (_, len) = expected.swap_remove(at);
+                let removed = collection.swap_remove(*key).map(|payload| payload.len());
+                assert_eq!(removed, Some(len), "{name} swap-removing {key} of {keys:?}");
+            }
+
+            let left = in_order(contents(&collection));
+            let expected = in_order(expected);
+            assert_eq!(left, expected, "{name} after churning {keys:?}");
+        }
+    }
+}
+
+/// The scans of `collections-iterate` and `collections-sum` add up every entry
+/// exactly once.
+struct Sums<'a>(&'a [usize]);
+
+impl Visitor for Sums<'_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let (name, keys) = (C::NAME, self.0);
+        let entries = entries(keys);
+        let collection = C::build(entries.clone());
+        let keys_sum: usize = entries
+            .iter()
+            .map(|(key, payload)| key + payload.len())
+            .sum();
+        // every payload byte is 1
+        let payloads_sum: u64 = entries
+            .iter()
+            .map(|(_, payload)| payload.len() as u64)
+            .sum();
+        assert_eq!(
+            collections::sum_keys(&collection),
+            keys_sum,
+            "{name} visiting {} keys",
+            keys.len()
+        );
+        assert_eq!(
+            collections::sum_payloads(&collection),
+            payloads_sum,
+            "{name} summing {} payloads",
+            keys.len()
+        );
+    }
+}
+
+/// Sequences and ordered maps iterate in their own order: insertion order, or
+/// key order for `BTreeMap`.
+struct Order<'a>(&'a [usize]);
+
+impl Visitor for Order<'_> {
+    fn visit<C: BenchCollection>(&mut self) {
+        let keys = self.0;
+        let collection = C::build(entries(keys));
+        let mut order = contents(&collection)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        let mut sorted = keys.to_vec();
+        sorted.sort_unstable();
+        match C::NAME {
+            "BTreeMap" => assert_eq!(order, sorted, "BTreeMap out of key order"),
+            name @ ("HashMap" | "FxHashMap" | "DashMap") => {
+                order.sort_unstable();
+                assert_eq!(order, sorted, "{name} lost entries");
+            }
+            name => assert_eq!(order, keys, "{name} out of insertion order"),
+        }
+    }
+}
+
+#[test]
+fn empty() {
+    collections::visit_all(&mut Find(&[]));
+}
+
+#[test]
+fn dense_keys_inline_and_spilled() {
+    // below, at and above the inline capacity of every SmallVec
+    for len in [1, 4, 8, 16, 32, 33, 100] {
+        collections::visit_all(&mut Find(&workload::dense_keys(len)));
+    }
+}
+
+#[test]
+fn random_keys() {
+    let mut rng = workload::rng();
+    for len in [10, 100, 1000] {
+        collections::visit_all(&mut Find(&workload::random_keys(&mut rng, len, 1_000_000)));
+    }
+}
+
+#[test]
+fn inserts_and_removes_match_a_btreemap() {
+    let mut rng = workload::rng();
+    for len in [1, 2, 9, 33, 100] {
+        let keys = workload::random_keys(&mut rng, len, 1_000_000);
+        collections::visit_all(&mut Update(&keys));
+    }
+}
+
+#[test]
+fn filled_updated_and_swap_removed() {
+    let mut rng = workload::rng();
+    for len in [0, 1, 9, 33, 100] {
+        let keys = workload::random_keys(&mut rng, len, 1_000_000);
+        collections::visit_all(&mut Churn(&keys));
+    }
+}
+
+#[test]
+fn iteration_visits_every_entry_in_order() {
+    let mut rng = workload::rng();
+    for len in [0, 5, 40] {
+        let keys = workload::random_keys(&mut rng, len, 1_000_000);
+        collections::visit_all(&mut Order(&keys));
+    }
+}
+
+#[test]
+fn scans_sum_every_entry_once() {
+    let mut rng = workload::rng();
+    // enough keys for every DashMap shard to hold several
+    for len in [0, 1, 33, 1000] {
+        let keys = workload::random_keys(&mut rng, len, 1_000_000);
+        collections::visit_all(&mut Sums(&keys));
+    }
+}
+
+#[test]
+fn variant_values_are_stable() {
+    // results on disk carry these values, so they must never change
+    fn codes<T: Copy + fmt::Display>(all: &[T], value: fn(T) -> usize) -> Vec<(String, usize)> {
+        all.iter().map(|&v| (v.to_string(), value(v))).collect()
+    }
+    let named = |codes: &[(&str, usize)]| {
+        codes
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        codes(&KeyPosition::ALL, KeyPosition::value),
+        named(&[("absent", 0), ("front", 1), ("middle", 2), ("back", 3)])
+    );
+    assert_eq!(
+        codes(&KeyDistribution::ALL, KeyDistribution::value),
+        named(&[
+            ("dense", 0),
+            ("sparse", 1),
+            ("clustered", 2),
+            ("power-of-two", 3),
+            ("fx-collisions", 4),
+        ])
+    );
+    assert_eq!(
+        codes(&LookupPattern::ALL, LookupPattern::value),
+        named(&[("uniform", 0), ("sequential", 1), ("zipf", 2)])
+    );
+    assert_eq!(
+        KeyDistribution::from_value(4),
+        Some(KeyDistribution::FxCollisions)
+    );
+    assert_eq!(KeyPosition::from_value(4), None);
+}
+
+#[test]
+fn variant_names_parse_back() {
+    fn round_trips<T>(all: &[T])
+    where
+        T: Copy + fmt::Debug + fmt::Display + PartialEq + FromStr<Err = String>,
+    {
+        for &variant in all {
+            assert_eq!(variant.to_string().parse::<T>(), Ok(variant));
+        }
+        assert!("Zipf".parse::<T>().is_err());
+        assert!("".parse::<T>().is_err());
+    }
+    round_trips(&KeyPosition::ALL);
+    round_trips(&KeyDistribution::ALL);
+    round_trips(&LookupPattern::ALL);
+    assert_eq!(
+        "zipfian".parse::<LookupPattern>(),
+        Err("unknown lookup pattern `zipfian`, expected uniform, sequential or zipf".to_string())
+    );
+}
+
+#[test]
+fn every_key_distribution() {
+    let mut rng = workload::rng();
+    for distribution in KeyDistribution::ALL {
+        for len in [0, 1, 65, 300] {
+            let keys = distribution.keys(&mut rng, len);
+            assert_eq!(keys.len(), len, "{distribution}");
+            let distinct = keys.iter().collect::<HashSet<_>>();
+            assert_eq!(distinct.len(), len, "{distribution} keys repeat");
+            collections::visit_all(&mut Find(&keys));
+        }
+    }
+}
+
+#[test]
+fn fx_collisions_share_bucket_and_control_byte() {
+    let keys = KeyDistribution::FxCollisions.keys(&mut workload::rng(), 100);
+    for key in keys {
+        let hash = FxBuildHasher.hash_one(key);
+        assert_eq!(hash & 0xffff_ffff, 0, "{key} hashes to {hash:x}");
+        assert_eq!(hash >> 57, 0, "{key} hashes to {hash:x}");
+    }
+}
+
+#[test]
+fn lookups_hit_as_declared() {
+    let mut rng = workload::rng();
+    let all_keys = workload::dense_keys(33 + 16);
+    let (keys, absent) = all_keys.split_at(33);
+    let collection = <Vec<Entry>>::build(entries(keys));
+    for position in KeyPosition::ALL {
+        for (hit_pct, expected_hits) in [(0, 0), (50, 8), (100, 16)] {
+            let lookups = workload::lookups(&mut rng, keys, absent, hit_pct, position);
+            let hits = lookups
+                .iter()
+                .filter(|key| collection.find(**key).is_some());
+            let expected_hits = match position {
+                KeyPosition::Absent => 0,
+                _ => expected_hits,
+            };
+            assert_eq!(hits.count(), expected_hits, "{hit_pct}% at {position}");
+            if let Some(key) = position.key(keys) {
+                assert!(
+                    lookups.iter().all(|k| *k == key || absent.contains(k)),
+                    "{position} hits another key"
+                );
+            }
+        }
+    }
+    assert_eq!(KeyPosition::Middle.key(keys), Some(16));
+    assert_eq!(KeyPosition::Back.key(keys), Some(32));
+    assert_eq!(KeyPosition::Front.key(&[]), None);
+}
+
+#[test]
+fn lookup_streams_follow_their_pattern() {
+    let mut rng = workload::rng();
+    let keys = workload::random_keys(&mut rng, 100, 1_000_000);
+    // share of the lookups taken by the most frequent key
+    let hottest = |stream: &[usize]| {
+        let mut counts = BTreeMap::new();
+        stream
+            .iter()
+            .for_each(|key| *counts.entry(key).or_insert(0) += 1);
+        *counts.values().max().unwrap() as f64 / stream.len() as f64
+    };
+
+    for pattern in LookupPattern::ALL {
+        let stream = workload::lookup_stream(&mut rng, &keys, 10_000, pattern, 1.0);
+        assert_eq!(stream.len(), 10_000, "{pattern}");
+        assert!(stream.iter().all(|key| keys.contains(key)), "{pattern}");
+    }
+    let sequential = workload::lookup_stream(&mut rng, &keys, 250, LookupPattern::Sequential, 0.0);
+    assert_eq!(sequential[..100], keys[..]);
+    assert_eq!(sequential[200..], keys[..50]);
+
+    let uniform = workload::lookup_stream(&mut rng, &keys, 10_000, LookupPattern::Uniform, 0.0);
+    let flat = workload::lookup_stream(&mut rng, &keys, 10_000, LookupPattern::Zipf, 0.0);
+    let skewed = workload::lookup_stream(&mut rng, &keys, 10_000, LookupPattern::Zipf, 1.5);
+    assert!(hottest(&uniform) < 0.03, "{}", hottest(&uniform));
+    assert!(hottest(&flat) < 0.03, "{}", hottest(&flat));
+    // 1 / ζ(1.5) truncated to 100 ranks is about 0.41
+    assert!(hottest(&skewed) > 0.35, "{}", hottest(&skewed));
+}
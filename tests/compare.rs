@@ -0,0 +1,63 @@
+use napkin_benches::{
+    compare,
+    groups::TOKIO_FIND,
+    results::{BenchResult, ConfidenceInterval, Estimate},
+};
+
+/// A `tokio-find` result of `function` at `values`, taking `mean_ns` give or
+/// take 1%.
+fn result(function: &str, values: &[usize], mean_ns: f64) -> BenchResult {
+    let value = TOKIO_FIND.params(values).to_string();
+    let estimate = Estimate {
+        confidence_interval: ConfidenceInterval {
+            confidence_level: 0.95,
+            lower_bound: mean_ns * 0.99,
+            upper_bound: mean_ns * 1.01,
+        },
+        point_estimate: mean_ns,
+        standard_error: mean_ns * 0.005,
+    };
+    BenchResult {
+        id: format!("{}/{function}/{value}", TOKIO_FIND.name),
+        group: TOKIO_FIND.name.to_string(),
+        function: Some(function.to_string()),
+        value: Some(value),
+        mean: estimate.clone(),
+        median: estimate.clone(),
+        std_dev: estimate,
+        alloc: None,
+        perf: None,
+        latency: None,
+    }
+}
+
+#[test]
+fn flips_match_extra_dimensions_on_the_shared_ones() {
+    let a = [
+        result("arc_mutex", &[10, 100], 300.0),
+        result("mpsc_ch", &[10, 100, 1], 600.0),
+        result("mpsc_ch", &[10, 100, 100], 310.0),
+    ];
+    let b = [
+        result("arc_mutex", &[10, 100], 300.0),
+        result("mpsc_ch", &[10, 100, 1], 150.0),
+        result("mpsc_ch", &[10, 100, 100], 310.0),
+    ];
+    let comparison = compare::compare(&a, &b);
+    assert_eq!(comparison.pairs.len(), 3);
+
+    let flips = comparison
+        .flips
+        .iter()
+        .map(|flip| (flip.parameter.as_str(), &*flip.first, &*flip.second))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flips,
+        [
+            ("10t100", "arc_mutex", "mpsc_ch (capacity=1)"),
+            ("10t100", "mpsc_ch (capacity=100)", "mpsc_ch (capacity=1)"),
+        ]
+    );
+    assert_eq!(comparison.flips[0].a_ratio, 2.0);
+    assert_eq!(comparison.flips[0].b_ratio, 2.0);
+}
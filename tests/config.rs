@@ -0,0 +1,147 @@
+use napkin_benches::config::{self, CONFIG_VAR, Matrix, SET_VAR};
+use std::{env, fs, panic};
+
+const TOML: &str = "
+[tokio-increment]
+tasks = [4, 8]
+increments = [10_000]
+";
+
+#[test]
+fn toml_and_overrides_parse() {
+    let matrix = Matrix::from_toml("napkin.toml", TOML).unwrap();
+    assert_eq!(matrix.get("tokio-increment", "tasks"), Some(&[4, 8][..]));
+    assert_eq!(
+        matrix.get("tokio-increment", "increments"),
+        Some(&[10_000][..])
+    );
+    assert_eq!(matrix.get("tokio-increment", "capacity"), None);
+
+    let matrix = Matrix::from_overrides(
+        SET_VAR,
+        " tokio-increment.tasks = 1, 2 ; memory-access.size_bytes=65_536;",
+    )
+    .unwrap();
+    assert_eq!(matrix.get("tokio-increment", "tasks"), Some(&[1, 2][..]));
+    assert_eq!(
+        matrix.get("memory-access", "size_bytes"),
+        Some(&[65_536][..])
+    );
+}
+
+#[test]
+fn later_matrices_replace_the_dimensions_they_set() {
+    let mut matrix = Matrix::from_toml("napkin.toml", TOML).unwrap();
+    matrix.merge(Matrix::from_overrides(SET_VAR, "tokio-increment.tasks=16").unwrap());
+    assert_eq!(matrix.get("tokio-increment", "tasks"), Some(&[16][..]));
+    assert_eq!(
+        matrix.get("tokio-increment", "increments"),
+        Some(&[10_000][..])
+    );
+}
+
+#[test]
+fn unknown_groups_and_dimensions_are_rejected() {
+    for (text, reason) in [
+        ("[tokio-incr]\ntasks = [4]", "unknown group `tokio-incr`"),
+        (
+            "[tokio-increment]\nthreads = [4]",
+            "`tokio-increment` has no dimension `threads`",
+        ),
+        (
+            "[tokio-increment]\ntasks = []",
+            "`tokio-increment.tasks` is empty",
+        ),
+    ] {
+        let error = Matrix::from_toml("napkin.toml", text).unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.starts_with("invalid parameter matrix in napkin.toml: "),
+            "{message}"
+        );
+        assert!(message.contains(reason), "{message}");
+    }
+    let error = Matrix::from_overrides(SET_VAR, "memory-access.size=64").unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("`memory-access` has no dimension `size`")
+    );
+}
+
+#[test]
+fn non_integer_values_are_rejected() {
+    for text in [
+        "[tokio-increment]\ntasks = [4.5]",
+        "[tokio-increment]\ntasks = [\"4\"]",
+        "[tokio-increment]\ntasks = [-1]",
+        "[tokio-increment]\ntasks = 4",
+    ] {
+        assert!(Matrix::from_toml("napkin.toml", text).is_err(), "{text}");
+    }
+    for text in [
+        "tokio-increment.tasks=4.5",
+        "tokio-increment.tasks=four",
+        "tokio-increment.tasks=-1",
+        "tokio-increment.tasks=4,",
+        "tokio-increment.tasks",
+        "tasks=4",
+    ] {
+        assert!(Matrix::from_overrides(SET_VAR, text).is_err(), "{text}");
+    }
+}
+
+// The only test touching the environment and working directory, which every
+// test of this binary shares.
+#[test]
+fn load_layers_the_file_the_env_file_and_the_overrides() {
+    let dir = env::temp_dir().join(format!("napkin-config-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("napkin.toml"),
+        "[tokio-increment]\ntasks = [1]\nincrements = [10]\n",
+    )
+    .unwrap();
+    fs::write(dir.join("other.toml"), "[tokio-increment]\ntasks = [2]\n").unwrap();
+    env::set_current_dir(&dir).unwrap();
+    let tasks = |matrix: &Matrix| matrix.get("tokio-increment", "tasks").map(<[_]>::to_vec);
+
+    // SAFETY: no other test of this binary reads or writes the environment
+    unsafe {
+        env::remove_var(CONFIG_VAR);
+        env::remove_var(SET_VAR);
+    }
+    let matrix = Matrix::load().unwrap();
+    assert_eq!(tasks(&matrix), Some(vec![1]));
+    assert_eq!(matrix.get("tokio-increment", "increments"), Some(&[10][..]));
+
+    // NAPKIN_CONFIG replaces napkin.toml
+    unsafe { env::set_var(CONFIG_VAR, dir.join("other.toml")) };
+    let matrix = Matrix::load().unwrap();
+    assert_eq!(tasks(&matrix), Some(vec![2]));
+    assert_eq!(matrix.get("tokio-increment", "increments"), None);
+
+    // NAPKIN_SET overrides both
+    unsafe { env::set_var(SET_VAR, "tokio-increment.tasks=3") };
+    assert_eq!(tasks(&Matrix::load().unwrap()), Some(vec![3]));
+    unsafe { env::remove_var(CONFIG_VAR) };
+    let matrix = Matrix::load().unwrap();
+    assert_eq!(tasks(&matrix), Some(vec![3]));
+    assert_eq!(matrix.get("tokio-increment", "increments"), Some(&[10][..]));
+
+    // an explicit file that does not exist is an error, unlike napkin.toml
+    unsafe { env::set_var(CONFIG_VAR, dir.join("missing.toml")) };
+    assert!(Matrix::load().is_err());
+
+    // and so is a typo, which `matrix` turns into a panic
+    unsafe {
+        env::remove_var(CONFIG_VAR);
+        env::set_var(SET_VAR, "tokio-increment.task=3");
+    }
+    assert!(Matrix::load().is_err());
+    let panic = panic::catch_unwind(config::matrix).unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains("has no dimension `task`"), "{message}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,83 @@
+use napkin_benches::{
+    gate::{Gate, Verdict},
+    results::{BenchResult, ConfidenceInterval, Estimate},
+};
+
+fn estimate(point: f64, (lower, upper): (f64, f64)) -> Estimate {
+    Estimate {
+        confidence_interval: ConfidenceInterval {
+            confidence_level: 0.95,
+            lower_bound: lower,
+            upper_bound: upper,
+        },
+        point_estimate: point,
+        standard_error: 0.0,
+    }
+}
+
+/// A measurement of `id` with mean `mean_ns`, its interval `ci` and a
+/// standard deviation of `std_dev_ns`.
+fn result(id: &str, mean_ns: f64, ci: (f64, f64), std_dev_ns: f64) -> BenchResult {
+    BenchResult {
+        id: id.to_string(),
+        group: "tokio-increment".to_string(),
+        function: Some("arc_mutex".to_string()),
+        value: Some("4t10000".to_string()),
+        mean: estimate(mean_ns, ci),
+        median: estimate(mean_ns, ci),
+        std_dev: estimate(std_dev_ns, (std_dev_ns, std_dev_ns)),
+        alloc: None,
+        perf: None,
+        latency: None,
+    }
+}
+
+const ID: &str = "tokio-increment/arc_mutex/4t10000";
+
+/// A baseline of 100 ns with 5% noise, so the default gate tolerates 7%.
+fn baseline() -> BenchResult {
+    result(ID, 100.0, (99.0, 101.0), 5.0)
+}
+
+#[test]
+fn regressions_within_the_noise_pass() {
+    let check = Gate::default().check_one(&baseline(), &result(ID, 106.0, (105.0, 107.0), 5.0));
+    assert!((check.limit.unwrap() - 0.07).abs() < 1e-9);
+    assert_eq!(check.verdict, Verdict::Unchanged);
+}
+
+#[test]
+fn regressions_beyond_the_noise_fail() {
+    let check = Gate::default().check_one(&baseline(), &result(ID, 110.0, (109.0, 111.0), 5.0));
+    assert!((check.change.unwrap() - 0.10).abs() < 1e-9);
+    assert_eq!(check.verdict, Verdict::Regressed);
+}
+
+#[test]
+fn overlapping_intervals_pass() {
+    let check = Gate::default().check_one(&baseline(), &result(ID, 110.0, (95.0, 125.0), 5.0));
+    assert_eq!(check.verdict, Verdict::Unchanged);
+}
+
+#[test]
+fn improvements_are_reported() {
+    let check = Gate::default().check_one(&baseline(), &result(ID, 80.0, (79.0, 81.0), 4.0));
+    assert_eq!(check.verdict, Verdict::Improved);
+}
+
+#[test]
+fn benchmarks_not_measured_again_are_missing() {
+    let other = "tokio-increment/arc_mutex/8t10000";
+    let checks = Gate::default().check(
+        &[baseline(), result(other, 200.0, (199.0, 201.0), 1.0)],
+        &[result(ID, 100.0, (99.0, 101.0), 5.0)],
+    );
+    assert_eq!(checks.len(), 2);
+    assert_eq!(checks[0].verdict, Verdict::Unchanged);
+    assert_eq!(checks[1].id, other);
+    assert_eq!(checks[1].verdict, Verdict::Missing);
+    assert_eq!(checks[1].current_ns, None);
+
+    let checks = Gate::default().check(&[baseline()], &[]);
+    assert_eq!(checks[0].verdict, Verdict::Missing);
+}
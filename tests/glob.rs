@@ -0,0 +1,96 @@
+use napkin_benches::glob::{self, Pattern};
+use regex::Regex;
+
+const IDS: &[&str] = &[
+    "tokio-find/mpsc_ch/4t10c1",
+    "tokio-find/arc_mutex/4t10",
+    "tokio-finder/arc_mutex/4t10",
+    "tokio-increment/arc_mutex/16t10000",
+    "collections-find/Vec Find/1KBx10h0p0k0",
+    "collections-find/SmallVec<8> Find/1KBx10h0p0k0",
+    "memory-access/Stride/65536",
+    "g/a.b/1",
+    "g/axb/1",
+    "g/x+y/1",
+    "g/xxy/1",
+    "g/f(1)/2",
+    "g/f1/2",
+    "g/[a]{2}|^$/3",
+];
+
+const PATTERNS: &[&str] = &[
+    "*",
+    "tokio-find",
+    "tokio-*/arc_mutex",
+    "tokio-*/arc_mutex/*",
+    "collections-find/Vec Find",
+    "collections-find/SmallVec<8> Find/*",
+    "*/Stride/6553?",
+    "g/a.b",
+    "g/x+y/*",
+    "g/f(1)",
+    "g/[a]{2}|^$",
+    "g/?/1",
+];
+
+#[test]
+fn criterion_filters_select_what_patterns_select() {
+    for &source in PATTERNS {
+        let pattern = Pattern::new(source);
+        let regex = Regex::new(&pattern.to_regex()).unwrap();
+        for &id in IDS {
+            assert_eq!(
+                regex.is_match(id),
+                pattern.selects(id),
+                "`{source}` on `{id}` with {regex}"
+            );
+        }
+    }
+
+    let patterns = PATTERNS.iter().map(|p| Pattern::new(p)).collect::<Vec<_>>();
+    let filter = Regex::new(&glob::filter_regex(&patterns[1..])).unwrap();
+    for &id in IDS {
+        let selected = patterns[1..].iter().any(|p| p.selects(id));
+        assert_eq!(filter.is_match(id), selected, "{id}");
+    }
+}
+
+#[test]
+fn stars_match_across_slashes() {
+    let pattern = Pattern::new("tokio-*/arc_mutex/*");
+    assert!(pattern.matches("tokio-find/arc_mutex/4t10"));
+    assert!(!pattern.matches("tokio-find/mpsc_ch/4t10c1"));
+    assert!(Pattern::new("*").matches(""));
+    assert!(Pattern::new("*c1").matches("tokio-find/mpsc_ch/4t10c1"));
+    assert!(Pattern::new("g/?/1").matches("g/a/1"));
+    assert!(!Pattern::new("g/?/1").matches("g/ab/1"));
+}
+
+#[test]
+fn metacharacters_match_themselves() {
+    assert!(Pattern::new("g/a.b").selects("g/a.b/1"));
+    assert!(!Pattern::new("g/a.b").selects("g/axb/1"));
+    assert!(!Pattern::new("g/x+y").selects("g/xxy/1"));
+    assert!(Pattern::new("g/f(1)").selects("g/f(1)/2"));
+    assert!(!Pattern::new("g/f(1)").selects("g/f1/2"));
+}
+
+#[test]
+fn groups_and_functions_select_everything_under_them() {
+    let group = Pattern::new("tokio-find");
+    assert!(group.selects("tokio-find/mpsc_ch/4t10c1"));
+    assert!(!group.selects("tokio-finder/arc_mutex/4t10"));
+    assert!(!group.matches("tokio-find/mpsc_ch/4t10c1"));
+
+    let function = Pattern::new("collections-find/Vec Find");
+    assert!(function.selects("collections-find/Vec Find/1KBx10h0p0k0"));
+    assert!(!function.selects("collections-find/Vec Finder/1KBx10h0p0k0"));
+
+    let pattern = Pattern::new("tokio-*/arc_mutex/*");
+    assert!(pattern.matches_prefix("tokio-find/"));
+    assert!(pattern.matches_prefix(""));
+    assert!(!pattern.matches_prefix("collections-find/"));
+    // a bare group selects its name instead, as no longer id matches it
+    assert!(Pattern::new("tokio-find").selects("tokio-find"));
+    assert!(!Pattern::new("tokio-find").matches_prefix("tokio-find/"));
+}
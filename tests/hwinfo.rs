@@ -0,0 +1,108 @@
+use napkin_benches::hwinfo::{self, Cache, HardwareProfile};
+use std::path::Path;
+
+/// `/proc` and `/sys` of a 4-CPU machine: two cores with two SMT siblings
+/// each, a private L1 and L2 per core and a shared L3.
+fn fixture() -> HardwareProfile {
+    HardwareProfile::from_root(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hwinfo"))
+}
+
+#[test]
+fn cpu_lists_parse() {
+    assert_eq!(
+        hwinfo::parse_cpu_list("0-3,8,10-11"),
+        [0, 1, 2, 3, 8, 10, 11]
+    );
+    assert_eq!(
+        hwinfo::parse_cpu_list("0-3,8,10-11\n"),
+        [0, 1, 2, 3, 8, 10, 11]
+    );
+    assert_eq!(hwinfo::parse_cpu_list("5"), [5]);
+    assert!(hwinfo::parse_cpu_list("").is_empty());
+    assert!(hwinfo::parse_cpu_list("\n").is_empty());
+}
+
+#[test]
+fn cache_sizes_parse() {
+    assert_eq!(hwinfo::parse_size("32K"), Some(32 * 1024));
+    assert_eq!(hwinfo::parse_size("1M\n"), Some(1024 * 1024));
+    assert_eq!(hwinfo::parse_size("8192K"), Some(8 * 1024 * 1024));
+    assert_eq!(hwinfo::parse_size("512"), Some(512));
+    assert_eq!(hwinfo::parse_size("32KB"), None);
+    assert_eq!(hwinfo::parse_size(""), None);
+}
+
+#[test]
+fn profiles_read_from_a_root() {
+    let profile = fixture();
+    assert_eq!(profile.vendor.as_deref(), Some("AuthenticAMD"));
+    assert_eq!(
+        profile.model_name.as_deref(),
+        Some("AMD Ryzen 7 7700X 8-Core Processor")
+    );
+    assert_eq!(profile.memory_bytes, Some(16_318_096 * 1024));
+    assert_eq!(profile.logical_cpus, 4);
+    assert_eq!(profile.cores, [vec![0, 1], vec![2, 3]]);
+    // no `cluster_cpus_list`, so clusters are the CPUs sharing an L2
+    assert_eq!(profile.clusters, [vec![0, 1], vec![2, 3]]);
+
+    // every cache once, though each CPU sharing it lists it
+    let caches = profile
+        .caches
+        .iter()
+        .map(|c| (c.level, c.kind.as_str(), c.size, c.shared_cpus.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        caches,
+        [
+            (1, "Data", 32 * 1024, vec![0, 1]),
+            (1, "Data", 32 * 1024, vec![2, 3]),
+            (1, "Instruction", 32 * 1024, vec![0, 1]),
+            (1, "Instruction", 32 * 1024, vec![2, 3]),
+            (2, "Unified", 1024 * 1024, vec![0, 1]),
+            (2, "Unified", 1024 * 1024, vec![2, 3]),
+            (3, "Unified", 8 * 1024 * 1024, vec![0, 1, 2, 3]),
+        ]
+    );
+    assert_eq!(
+        profile.data_cache(3),
+        Some(&Cache {
+            level: 3,
+            kind: "Unified".to_string(),
+            size: 8 * 1024 * 1024,
+            line_size: Some(64),
+            ways: Some(16),
+            shared_cpus: vec![0, 1, 2, 3],
+        })
+    );
+    assert_eq!(
+        profile.cache_sweep(usize::MAX),
+        Some(vec![
+            16 * 1024,
+            32 * 1024,
+            64 * 1024,
+            512 * 1024,
+            1024 * 1024,
+            2 * 1024 * 1024,
+            4 * 1024 * 1024,
+            8 * 1024 * 1024,
+            16 * 1024 * 1024,
+        ])
+    );
+
+    let fingerprint = profile.fingerprint();
+    assert!(
+        fingerprint.starts_with("amd-ryzen-7-7700x-8-core-processor-"),
+        "{fingerprint}"
+    );
+    assert_eq!(fingerprint, fixture().fingerprint());
+}
+
+#[test]
+fn missing_roots_leave_the_profile_empty() {
+    let profile = HardwareProfile::from_root(Path::new("/nonexistent"));
+    assert_eq!(profile.logical_cpus, 0);
+    assert!(profile.caches.is_empty());
+    assert_eq!(profile.cache_sweep(usize::MAX), None);
+    assert_eq!(profile.memory_budget(2), 1024 * 1024 * 1024);
+}
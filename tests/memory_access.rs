@@ -0,0 +1,41 @@
+use napkin_benches::{
+    groups::MEMORY_ACCESS_STRIDE,
+    impls::memory_access::{random_access, sequential_access, stride_access},
+    workload,
+};
+
+const SIZES: [usize; 4] = [1, 63, 64, 4096 + 7];
+
+#[test]
+fn sequential_and_random_visit_every_byte_once() {
+    let mut rng = workload::rng();
+    for size in SIZES {
+        let start = (0..size).map(|i| i as u8).collect::<Vec<_>>();
+
+        let mut sequential = start.clone();
+        sequential_access(&mut sequential);
+        let mut random = start.clone();
+        random_access(&mut random, &workload::access_order(&mut rng, size));
+
+        assert_eq!(sequential, random, "size {size}");
+        let expected = start.iter().map(|b| b.wrapping_add(1)).collect::<Vec<_>>();
+        assert_eq!(sequential, expected, "size {size}");
+    }
+}
+
+#[test]
+fn stride_visits_one_byte_per_stride() {
+    for size in SIZES {
+        let mut data = vec![255u8; size];
+        stride_access(&mut data, MEMORY_ACCESS_STRIDE);
+
+        for (i, &byte) in data.iter().enumerate() {
+            let expected = if i % MEMORY_ACCESS_STRIDE == 0 {
+                0
+            } else {
+                255
+            };
+            assert_eq!(byte, expected, "byte {i} of {size}");
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use napkin_benches::groups::{
+    COLLECTIONS_FIND, COLLECTIONS_LOOKUPS, GROUPS, TOKIO_FIND, TOKIO_INCREMENT,
+};
+
+#[test]
+fn every_group_round_trips() {
+    for group in GROUPS {
+        // every prefix of the dimensions, as trailing ones are optional
+        for len in 1..=group.dims.len() {
+            let values = (0..len).map(|i| 10 * i + 3).collect::<Vec<_>>();
+            let params = group.params(&values);
+            let text = params.to_string();
+            let parsed = group
+                .parse(&text)
+                .unwrap_or_else(|e| panic!("{}: {e}", group.name));
+            assert_eq!(parsed, params, "{} `{text}`", group.name);
+            assert_eq!(parsed.iter().map(|(_, v)| v).collect::<Vec<_>>(), values);
+        }
+    }
+}
+
+#[test]
+fn compact_forms_parse_into_named_values() {
+    let find = COLLECTIONS_FIND.parse("8KBx10h0p0k0").unwrap();
+    assert_eq!(
+        find.iter().collect::<Vec<_>>(),
+        [
+            ("payload_kb", 8),
+            ("length", 10),
+            ("hit_pct", 0),
+            ("position", 0),
+            ("keys", 0),
+        ]
+    );
+    assert_eq!(find.to_string(), "8KBx10h0p0k0");
+
+    let increment = TOKIO_INCREMENT.parse("4t10000c1").unwrap();
+    assert_eq!(increment.get("tasks"), Some(4));
+    assert_eq!(increment.get("increments"), Some(10_000));
+    assert_eq!(increment.get("capacity"), Some(1));
+
+    let without_capacity = TOKIO_INCREMENT.parse("4t10000").unwrap();
+    assert_eq!(without_capacity.get("capacity"), None);
+    assert_eq!(without_capacity.to_string(), "4t10000");
+
+    let lookups = COLLECTIONS_LOOKUPS.parse("100a2s99").unwrap();
+    assert_eq!(lookups.get("skew_pct"), Some(99));
+}
+
+#[test]
+fn malformed_parameters_are_rejected() {
+    for (text, reason) in [
+        // wrong prefix
+        ("4x10000", "expected `t<"),
+        ("8KBy10", "expected `KBx<length>`"),
+        // missing value
+        ("4t", "expected `t<"),
+        ("", "expected `<tasks>`"),
+        ("4tc1", "expected `t<"),
+        // trailing garbage
+        ("4t10000c1zz", "unexpected trailing `zz`"),
+        ("4t10000c1c2", "unexpected trailing `c2`"),
+    ] {
+        let group = match text.contains("KB") {
+            true => &COLLECTIONS_FIND,
+            false => &TOKIO_FIND,
+        };
+        let error = group
+            .parse(text)
+            .expect_err(&format!("`{text}` should not parse"));
+        let message = error.to_string();
+        assert!(
+            message.starts_with(&format!("invalid parameters `{text}`: ")),
+            "{message}"
+        );
+        assert!(message.contains(reason), "`{text}`: {message}");
+    }
+}
@@ -0,0 +1,46 @@
+use napkin_benches::{
+    config::SET_VAR,
+    groups::TOKIO_INCREMENT,
+    profile::{PROFILE_VAR, Profile},
+};
+use std::env;
+
+#[test]
+fn quick_keeps_the_endpoints_of_every_sweep() {
+    let quick = Profile::Quick;
+    assert_eq!(quick.thin(&[]), Vec::<usize>::new());
+    assert_eq!(quick.thin(&[7]), [7]);
+    assert_eq!(quick.thin(&[10, 100]), [10, 100]);
+    assert_eq!(quick.thin(&[1000, 10, 100]), [10, 1000]);
+    assert_eq!(quick.thin(&[1, 2, 3, 4, 5, 6, 7, 8, 9]), [1, 5, 9]);
+    assert_eq!(quick.thin(&(1..=13).collect::<Vec<_>>()), [1, 5, 9, 13]);
+    for len in 2..40 {
+        let values = (0..len).map(|i| i * 10).collect::<Vec<_>>();
+        let thinned = quick.thin(&values);
+        assert_eq!(thinned.first(), values.first(), "{len} values");
+        assert_eq!(thinned.last(), values.last(), "{len} values");
+        assert!(thinned.is_sorted(), "{len} values: {thinned:?}");
+        assert_eq!(thinned.len(), len.div_ceil(4).max(2), "{len} values");
+    }
+    assert_eq!(Profile::Full.thin(&[1000, 10, 100]), [1000, 10, 100]);
+}
+
+// The only test touching the environment, which every test of this binary
+// shares, and which both the profile and the matrix read once.
+#[test]
+fn quick_sweeps_leave_configured_values_alone() {
+    // SAFETY: no other test of this binary reads or writes the environment
+    unsafe {
+        env::set_var(PROFILE_VAR, "quick");
+        env::set_var(SET_VAR, "tokio-increment.tasks=1,2,3,4,5,6");
+    }
+    assert_eq!(Profile::current(), Profile::Quick);
+    assert_eq!(
+        TOKIO_INCREMENT.sweep("tasks", &[4, 16, 64]),
+        [1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(
+        TOKIO_INCREMENT.sweep("increments", &[10, 100, 1000, 10_000]),
+        [10, 10_000]
+    );
+}
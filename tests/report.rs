@@ -0,0 +1,144 @@
+use napkin_benches::{
+    complexity::{self, MIN_LOG_POINTS, Model, Verdict},
+    export::{self, Row, Stat},
+    groups::{COLLECTIONS_FIND, COLLECTIONS_LOOKUPS, Group, TOKIO_FIND},
+    report,
+};
+
+/// A `tokio-find` row of `implementation` at `values`, taking `mean_ns`.
+fn row(implementation: &str, values: &[usize], mean_ns: f64) -> Row {
+    group_row(&TOKIO_FIND, implementation, values, mean_ns)
+}
+
+fn group_row(group: &Group, implementation: &str, values: &[usize], mean_ns: f64) -> Row {
+    let params = group.params(values);
+    let stat = Stat {
+        point: mean_ns,
+        lower: mean_ns,
+        upper: mean_ns,
+    };
+    Row {
+        group: group.name.to_string(),
+        implementation: implementation.to_string(),
+        parameter: params.to_string(),
+        params: params.iter().collect(),
+        work: (group.work)(&params),
+        mean: stat,
+        median: stat,
+        std_dev: stat,
+        alloc: None,
+        perf: None,
+        latency: None,
+    }
+}
+
+#[test]
+fn extra_dimensions_rank_with_the_shared_ones() {
+    let rows = [
+        row("arc_mutex", &[10, 100], 300.0),
+        row("mpsc_ch", &[10, 100, 1], 400.0),
+        row("mpsc_ch", &[10, 100, 100], 200.0),
+        row("arc_mutex", &[100, 100], 900.0),
+    ];
+    let points = report::points(&rows);
+    assert_eq!(points.len(), 2);
+
+    let point = &points[0];
+    assert_eq!(point.parameter, "10t100");
+    assert_eq!(point.dims(), [("tasks", 10), ("indices", 100)]);
+    let labels = point
+        .rows
+        .iter()
+        .map(|row| point.label(row))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            "mpsc_ch (capacity=100)",
+            "arc_mutex",
+            "mpsc_ch (capacity=1)"
+        ]
+    );
+    assert_eq!(point.ratio(point.slowest()), 2.0);
+
+    let markdown = report::markdown(&rows, None);
+    assert!(markdown.contains("#### `10t100` (tasks=10, indices=100)"));
+    assert!(markdown.contains("| arc_mutex | 300.00 ns |"));
+    assert!(markdown.contains("| mpsc_ch (capacity=1) | 400.00 ns |"));
+}
+
+#[test]
+fn variants_are_named() {
+    let rows = [group_row(
+        &COLLECTIONS_FIND,
+        "Vec Find",
+        &[1, 100, 50, 3, 4],
+        100.0,
+    )];
+    let markdown = report::markdown(&rows, None);
+    assert!(markdown.contains(
+        "#### `1KBx100h50p3k4` (payload_kb=1, length=100, hit_pct=50, position=back, \
+         keys=fx-collisions)"
+    ));
+
+    let mut csv = Vec::new();
+    export::write_csv(&rows, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert!(csv.contains(",1,100,50,back,fx-collisions,"));
+    let json = export::to_json(&rows);
+    assert_eq!(json[0]["keys"], "fx-collisions");
+    assert_eq!(json[0]["length"], 100);
+}
+
+#[test]
+fn csv_and_json_share_their_columns() {
+    let rows = [
+        row("arc_mutex", &[4, 10], 1000.0),
+        row("mpsc_ch", &[4, 10, 1], 1500.5),
+    ];
+    let mut csv = Vec::new();
+    export::write_csv(&rows, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let json = export::to_json(&rows);
+
+    let mut lines = csv.lines();
+    let header = lines.next().unwrap().split(',').collect::<Vec<_>>();
+    for (line, object) in lines.zip(json.as_array().unwrap()) {
+        let object = object.as_object().unwrap();
+        assert_eq!(header, object.keys().collect::<Vec<_>>());
+        for (field, (_, value)) in line.split(',').zip(object) {
+            match value {
+                serde_json::Value::Null => assert_eq!(field, ""),
+                serde_json::Value::String(text) => assert_eq!(field, text),
+                number => assert_eq!(field.parse::<f64>().ok(), number.as_f64()),
+            }
+        }
+    }
+    assert_eq!(json[1]["capacity"], 1);
+    assert_eq!(json[0]["capacity"], serde_json::Value::Null);
+}
+
+#[test]
+fn log_series_are_judged_only_over_enough_sizes() {
+    // a lookup dominated by its constant part, growing by a fifth over the sweep
+    let series = |lengths: &[usize]| -> Vec<Row> {
+        lengths
+            .iter()
+            .map(|&n| {
+                let mean_ns = 100.0 + 2.0 * (n as f64).ln();
+                group_row(&COLLECTIONS_LOOKUPS, "BTreeMap Find", &[n, 0, 0], mean_ns)
+            })
+            .collect()
+    };
+
+    let short = complexity::fits(&series(&[10, 100, 1000]));
+    assert_eq!(short.len(), 1);
+    assert_eq!(short[0].verdict(), Some(Verdict::TooFewSizes));
+
+    let lengths = [10, 100, 1000, 10_000, 100_000];
+    assert!(lengths.len() >= MIN_LOG_POINTS);
+    let long = complexity::fits(&series(&lengths));
+    assert_eq!(long.len(), 1);
+    assert_eq!(long[0].curve.model, Model::Log);
+    assert_eq!(long[0].verdict(), None);
+}
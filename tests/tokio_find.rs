@@ -0,0 +1,128 @@
+use napkin_benches::{
+    groups::TOKIO_FIND_PAYLOAD,
+    impls::tokio_find::{arc_mutex, broadcast, dash_map, mpsc, unbounded},
+    latency::{NoRecorder, Record, Recorder},
+    workload::{self, Payload},
+};
+use std::collections::BTreeSet;
+use tokio::runtime::{Builder, Runtime};
+
+/// The current-thread runtime the groups use by default, and a
+/// multi-threaded one like `NAPKIN_WORKER_CPUS` sets up.
+fn runtimes() -> [Runtime; 2] {
+    [
+        Builder::new_current_thread().enable_all().build().unwrap(),
+        Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap(),
+    ]
+}
+
+/// Entries of `keys` with non-zero payloads, so zeroing them is observable.
+fn values(keys: &[usize]) -> Vec<(usize, Payload)> {
+    workload::entries(keys, &workload::payload(TOKIO_FIND_PAYLOAD, 1)).collect()
+}
+
+/// `values` with the payload of every targeted key zeroed, sorted by key.
+fn expected(values: &[(usize, Payload)], indices_per_task: &[Vec<usize>]) -> Vec<(usize, Payload)> {
+    let targeted = indices_per_task.iter().flatten().collect::<BTreeSet<_>>();
+    let mut expected = values
+        .iter()
+        .map(|(key, data)| match targeted.contains(key) {
+            true => (*key, vec![0; data.len()]),
+            false => (*key, data.clone()),
+        })
+        .collect::<Vec<_>>();
+    expected.sort();
+    expected
+}
+
+fn sorted(mut entries: Vec<(usize, Payload)>) -> Vec<(usize, Payload)> {
+    entries.sort();
+    entries
+}
+
+/// Runs every strategy with `R` timing its operations, as Criterion
+/// (`NoRecorder`) or the latency pass (`Recorder`) would.
+fn check<R: Record>(
+    rt: &Runtime,
+    values: &[(usize, Payload)],
+    indices_per_task: &[Vec<usize>],
+    with_broadcast: bool,
+) {
+    let expected = expected(values, indices_per_task);
+    let n = indices_per_task.len();
+    let (v, i) = (|| values.to_vec(), || indices_per_task.to_vec());
+
+    let mut results = rt.block_on(async {
+        vec![
+            ("arc_mutex", arc_mutex::<R>(n, Vec::new(), v(), i()).await),
+            (
+                "dashmap",
+                dash_map::<R>(n, Vec::new(), v(), i())
+                    .await
+                    .into_iter()
+                    .collect(),
+            ),
+            ("unbounded_ch", unbounded::<R>(n, v(), i()).await),
+            ("mpsc_ch c1", mpsc::<R>(n, v(), i(), 1).await),
+            ("mpsc_ch c100", mpsc::<R>(n, v(), i(), 100).await),
+        ]
+    });
+    if with_broadcast {
+        results.push(("broadcast_ch", rt.block_on(broadcast::<R>(n, v(), i()))));
+    }
+
+    for (name, entries) in results {
+        assert!(
+            sorted(entries) == expected,
+            "{name} with {n} tasks left the wrong entries"
+        );
+    }
+}
+
+#[test]
+fn every_task_zeroes_every_key() {
+    let mut rng = workload::rng();
+    for rt in runtimes() {
+        for (tasks, indices) in [(1, 1), (10, 100), (100, 10)] {
+            let keys = workload::random_keys(&mut rng, indices, 1_000_000);
+            let values = values(&workload::shuffled(&mut rng, &keys));
+            let indices_per_task = workload::indices_per_task(&mut rng, &keys, tasks);
+            check::<NoRecorder>(&rt, &values, &indices_per_task, false);
+            check::<Recorder>(&rt, &values, &indices_per_task, false);
+        }
+    }
+}
+
+#[test]
+fn untargeted_keys_are_left_alone() {
+    let mut rng = workload::rng();
+    let keys = workload::random_keys(&mut rng, 100, 1_000_000);
+    let values = values(&keys);
+    // four tasks, each targeting a fifth of the keys
+    let indices_per_task = keys
+        .chunks(20)
+        .take(4)
+        .map(<[usize]>::to_vec)
+        .collect::<Vec<_>>();
+    for rt in runtimes() {
+        check::<NoRecorder>(&rt, &values, &indices_per_task, false);
+    }
+}
+
+// separate, as every call allocates the whole broadcast channel
+#[test]
+fn broadcast_zeroes_every_targeted_key() {
+    let mut rng = workload::rng();
+    let keys = workload::random_keys(&mut rng, 100, 1_000_000);
+    let values = values(&keys);
+    let mut indices_per_task = workload::indices_per_task(&mut rng, &keys, 10);
+    indices_per_task[0].truncate(50);
+    indices_per_task.truncate(1);
+    for rt in runtimes() {
+        check::<NoRecorder>(&rt, &values, &indices_per_task, true);
+    }
+}
@@ -0,0 +1,62 @@
+use napkin_benches::{
+    impls::tokio_increment::{arc_mutex, atomic_usize, broadcast, mpsc, unbounded},
+    latency::{NoRecorder, Record, Recorder},
+};
+use tokio::runtime::{Builder, Runtime};
+
+/// The current-thread runtime the groups use by default, and a
+/// multi-threaded one like `NAPKIN_WORKER_CPUS` sets up.
+fn runtimes() -> [Runtime; 2] {
+    [
+        Builder::new_current_thread().enable_all().build().unwrap(),
+        Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap(),
+    ]
+}
+
+/// Runs every counter with `R` timing its operations, as Criterion
+/// (`NoRecorder`) or the latency pass (`Recorder`) would.
+fn check<R: Record>(rt: &Runtime, tasks: usize, increments: usize) {
+    let expected = tasks * increments;
+    let counts = rt.block_on(async {
+        vec![
+            (
+                "atomic_usize",
+                atomic_usize(tasks, increments, Vec::new()).await,
+            ),
+            (
+                "arc_mutex",
+                arc_mutex::<R>(tasks, increments, Vec::new()).await,
+            ),
+            ("unbounded_ch", unbounded::<R>(tasks, increments).await),
+            ("mpsc_ch c1", mpsc::<R>(tasks, increments, 1).await),
+            ("mpsc_ch c100", mpsc::<R>(tasks, increments, 100).await),
+        ]
+    });
+    for (name, count) in counts {
+        assert_eq!(count, expected, "{name} with {tasks}t{increments}");
+    }
+}
+
+#[test]
+fn every_increment_is_counted() {
+    for rt in runtimes() {
+        for (tasks, increments) in [(1, 1), (4, 100), (16, 1000)] {
+            check::<NoRecorder>(&rt, tasks, increments);
+            check::<Recorder>(&rt, tasks, increments);
+        }
+    }
+}
+
+// separate, as every call allocates the whole broadcast channel
+#[test]
+fn every_broadcast_is_counted() {
+    for rt in runtimes() {
+        let (tasks, increments) = (8, 1000);
+        let count = rt.block_on(broadcast::<NoRecorder>(tasks, increments));
+        assert_eq!(count, tasks * increments, "broadcast_ch");
+    }
+}